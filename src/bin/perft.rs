@@ -0,0 +1,53 @@
+//! Counts move generation paths to check the move generator.
+//!
+//! Usage: `perft [--divide] <depth> [game.pgn4]`
+//!
+//! The position and rules come from the tags of the PGN4 file ("Variant", "RuleVariants"
+//! and "StartFen4"); without a file the default free-for-all start is used.
+
+use pgn4::{divide, perft, Replay, Variant, PGN4};
+use std::process::exit;
+
+fn main() {
+    let mut split = false;
+    let mut args = Vec::new();
+    for arg in std::env::args().skip(1) {
+        if arg == "--divide" {
+            split = true;
+        } else {
+            args.push(arg);
+        }
+    }
+    let depth = match args.first().map(|d| d.parse::<usize>()) {
+        Some(Ok(depth)) if args.len() <= 2 => depth,
+        _ => {
+            eprintln!("Usage: perft [--divide] <depth> [game.pgn4]");
+            exit(2);
+        }
+    };
+    let variant = match args.get(1) {
+        Some(file) => read_variant(file).unwrap_or_else(|e| {
+            eprintln!("{}: {}", file, e);
+            exit(1);
+        }),
+        None => Variant::ffa_default(),
+    };
+    let replay = Replay::new(variant);
+    if split {
+        let mut total = 0;
+        for (m, count) in divide(&replay, depth) {
+            println!("{}: {}", m, count);
+            total += count;
+        }
+        println!();
+        println!("{}", total);
+    } else {
+        println!("{}", perft(&replay, depth));
+    }
+}
+
+fn read_variant(file: &str) -> Result<Variant, String> {
+    let text = std::fs::read_to_string(file).map_err(|e| e.to_string())?;
+    let pgn: PGN4 = text.parse().map_err(|e: pgn4::PGN4Error| e.to_string())?;
+    pgn.variant().map_err(|e| e.to_string())
+}
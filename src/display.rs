@@ -1,6 +1,7 @@
 use std::fmt;
 
 use crate::types::*;
+use crate::writer::{WriteOptions, Writer};
 
 impl fmt::Display for BasicMove {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -87,71 +88,18 @@ impl fmt::Debug for QuarterTurn {
 }
 impl fmt::Display for Turn {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.number != 0 {
-            write!(f, "{}", self.number)?;
-        }
-        if self.double_dot {
-            write!(f, ".. ")?;
-        } else {
-            write!(f, ". ")?;
-        }
-        write!(f, "{}", self.turns[0])?;
-        for quarter in &self.turns[1..] {
-            write!(f, " .. {}", quarter)?;
-        }
-        Ok(())
+        Writer::new(f, &WriteOptions::chess_com()).turn(self, None)
     }
 }
 
 impl fmt::Display for QuarterTurn {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.main)?;
-        if let Some(modifier) = &self.modifier {
-            write!(f, "{}", modifier)?;
-        }
-        if self.extra_stalemate {
-            write!(f, "S")?;
-        }
-        if let Some(d) = &self.description {
-            write!(f, " {{ {} }}", d)?;
-        }
-        for alt in &self.alternatives {
-            // Just ignore it if len is 0 and it is for some reason instantiated.
-            if alt.len() != 0 {
-                // The initial '(' has different whitespace if the alternative starts
-                // in the middle of a turn version the beginning
-                if alt[0].number == 0 {
-                    write!(f, " ( ")?;
-                } else {
-                    write!(f, "\n(")?;
-                }
-                // Write \n separated turns
-                write!(f, "{}", alt[0])?;
-                for turn in &alt[1..] {
-                    write!(f, "\n{}", turn)?;
-                }
-                // The ending always has space on both sides
-                write!(f, " ) ")?;
-            }
-        }
-        Ok(())
+        Writer::new(f, &WriteOptions::chess_com()).quarter(self, None)
     }
 }
 
 impl fmt::Display for PGN4 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.bracketed.len() != 0 {
-            for bracket in &self.bracketed {
-                write!(f, "[{} \"{}\"]\n", bracket.0, bracket.1)?;
-            }
-            write!(f, "\n\n\n")?;
-        }
-        if self.turns.len() != 0 {
-            write!(f, "{}", self.turns[0])?;
-            for turn in &self.turns[1..] {
-                write!(f, "\n{}", turn)?;
-            }
-        }
-        Ok(())
+        Writer::new(f, &WriteOptions::chess_com()).pgn(self, None)
     }
 }
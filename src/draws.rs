@@ -0,0 +1,104 @@
+use crate::movegen::*;
+use crate::replay::{PositionError, Replay};
+use crate::rules::*;
+use crate::types::*;
+use fen4::{Board, Color, Piece};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+/// A reason the game could be claimed as a draw
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DrawCondition {
+    /// The position occurred for the given time (at least the third)
+    Repetition(usize),
+    /// Every player still in the game made 50 moves without a capture or a pawn move
+    FiftyMoves,
+    /// No player has the material to checkmate anyone
+    InsufficientMaterial,
+}
+
+/// Quarter turns per player that count towards [`DrawCondition::FiftyMoves`]
+pub const FIFTY_MOVES: usize = 50;
+
+/// Remembers the positions of a line being replayed to find [`DrawCondition`]s
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DrawDetector {
+    seen: HashMap<u64, usize>,
+}
+
+impl DrawDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Records the current position of `replay` and returns the draw conditions it meets.
+    ///
+    /// Should be called once for the starting position and then after every quarter turn.
+    pub fn observe(&mut self, replay: &Replay) -> Vec<DrawCondition> {
+        let mut out = Vec::new();
+        let count = self.seen.entry(replay.hash()).or_insert(0);
+        *count += 1;
+        if *count >= 3 {
+            out.push(DrawCondition::Repetition(*count));
+        }
+        if fifty_moves(replay.board(), replay.variant()) {
+            out.push(DrawCondition::FiftyMoves);
+        }
+        if insufficient_material(replay.board(), replay.variant()) {
+            out.push(DrawCondition::InsufficientMaterial);
+        }
+        out
+    }
+}
+
+/// Has every player still in play moved [`FIFTY_MOVES`] times since the last capture or pawn move
+pub fn fifty_moves(board: &Board, variant: &Variant) -> bool {
+    let players = COLORS
+        .iter()
+        .filter(|c| in_play(board, variant, **c))
+        .count();
+    players > 0 && board.draw_ply >= FIFTY_MOVES * players
+}
+
+/// Are the only living pieces kings, apart from at most a single knight or bishop.
+///
+/// Only applies where kings can be checkmated, so never in "Antichess" or "CaptureTheKing".
+/// Kings alone can still win by reaching the hill in "KotH" or by checking in n-check.
+pub fn insufficient_material(board: &Board, variant: &Variant) -> bool {
+    if !royal_rules(variant) || variant.king_of_the_hill || variant.ncheck > 0 {
+        return false;
+    }
+    let mut minors = 0;
+    for row in board.board.iter() {
+        for piece in row.iter() {
+            match piece {
+                Piece::Normal(Color::Turn(_), 'K') => {}
+                Piece::Normal(Color::Turn(_), 'N') | Piece::Normal(Color::Turn(_), 'B') => {
+                    minors += 1
+                }
+                Piece::Normal(Color::Turn(_), _) => return false,
+                _ => {}
+            }
+        }
+    }
+    minors <= 1
+}
+
+impl PGN4 {
+    /// The draw conditions met after each quarter turn of the mainline.
+    ///
+    /// The first entry is for the starting position, so there is one more entry than
+    /// there are quarter turns.
+    pub fn draw_conditions(&self) -> Result<Vec<Vec<DrawCondition>>, PositionError> {
+        let mut replay = Replay::new(self.variant()?);
+        let mut detector = DrawDetector::new();
+        let mut out = vec![detector.observe(&replay)];
+        for qturn in self.turns.iter().flat_map(|t| &t.turns) {
+            replay.play(qturn)?;
+            out.push(detector.observe(&replay));
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,157 @@
+use crate::path::Path;
+use crate::types::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// What to do with variations inside of variations when flattening
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FlattenPolicy {
+    /// Turn them into variations of the mainline which replay the moves leading up to them
+    Hoist,
+    /// Remove them
+    Drop,
+}
+
+/// A nested variation that was changed by [`PGN4::flatten_variations`]
+///
+/// Paths point at the first move of a variation.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Flattened {
+    /// The variation that was at `from` is now a variation of the mainline at `to`
+    Hoisted { from: Path, to: Path },
+    /// The variation at this path was removed, together with any variations inside of it
+    Dropped(Path),
+}
+
+/// A variation of the mainline after flattening and the paths of the nested variations in it
+struct FlatLine {
+    turns: Vec<Turn>,
+    /// Path of the first move relative to the line this was nested in, and the number of
+    /// moves before it in `turns`
+    origin: Option<(Vec<usize>, usize)>,
+}
+
+impl PGN4 {
+    /// Makes sure no variation contains another variation, which Chess.com requires.
+    ///
+    /// With [`FlattenPolicy::Hoist`] a nested variation becomes another variation of the same
+    /// mainline move as the variation it was in. The moves of the outer variation that came
+    /// before it are repeated at its start.
+    pub fn flatten_variations(&mut self, policy: FlattenPolicy) -> Vec<Flattened> {
+        let mut changes = Vec::new();
+        let mut ply = 0;
+        for turn in &mut self.turns {
+            for qturn in &mut turn.turns {
+                ply += 1;
+                let mut alternatives = Vec::new();
+                for (i, alt) in std::mem::take(&mut qturn.alternatives)
+                    .into_iter()
+                    .enumerate()
+                {
+                    let base = vec![ply, i + 1];
+                    for line in flatten_line(alt, policy, &base, &mut changes) {
+                        if let Some((from, before)) = line.origin {
+                            let mut from_path = base.clone();
+                            from_path.extend(from);
+                            changes.push(Flattened::Hoisted {
+                                from: Path(from_path),
+                                to: Path(vec![ply, alternatives.len() + 1, before + 1]),
+                            });
+                        }
+                        alternatives.push(line.turns);
+                    }
+                }
+                qturn.alternatives = alternatives;
+            }
+        }
+        changes
+    }
+}
+
+/// Removes the variations from the line and returns it followed by the hoisted variations.
+/// `base` is the path to the line, used for reporting dropped variations.
+fn flatten_line(
+    mut turns: Vec<Turn>,
+    policy: FlattenPolicy,
+    base: &[usize],
+    changes: &mut Vec<Flattened>,
+) -> Vec<FlatLine> {
+    let mut hoisted = Vec::new();
+    let mut ply = 0;
+    for turn_index in 0..turns.len() {
+        for q_index in 0..turns[turn_index].turns.len() {
+            ply += 1;
+            let nested = std::mem::take(&mut turns[turn_index].turns[q_index].alternatives);
+            for (i, alt) in nested.into_iter().enumerate() {
+                let mut path = base.to_vec();
+                path.extend(&[ply, i + 1]);
+                if policy == FlattenPolicy::Drop {
+                    path.push(1);
+                    changes.push(Flattened::Dropped(Path(path)));
+                    continue;
+                }
+                let prefix = line_before(&turns, ply - 1);
+                for line in flatten_line(alt, policy, &path, changes) {
+                    let mut from = vec![ply, i + 1];
+                    let before = match line.origin {
+                        Some((rest, before)) => {
+                            from.extend(rest);
+                            before
+                        }
+                        None => {
+                            from.push(1);
+                            0
+                        }
+                    };
+                    hoisted.push(FlatLine {
+                        turns: join(prefix.clone(), line.turns),
+                        origin: Some((from, ply - 1 + before)),
+                    });
+                }
+            }
+        }
+    }
+    let mut out = vec![FlatLine {
+        turns,
+        origin: None,
+    }];
+    out.extend(hoisted);
+    out
+}
+
+/// The first `count` quarter turns of a line (which has no variations left)
+fn line_before(turns: &[Turn], count: usize) -> Vec<Turn> {
+    let mut out = Vec::new();
+    let mut left = count;
+    for turn in turns {
+        if left == 0 {
+            break;
+        }
+        let taken = left.min(turn.turns.len());
+        out.push(Turn {
+            turns: turn.turns[..taken].to_vec(),
+            ..turn.clone()
+        });
+        left -= taken;
+    }
+    out
+}
+
+/// Continues `prefix` with `rest`, which starts from the position at the end of `prefix`
+fn join(mut prefix: Vec<Turn>, mut rest: Vec<Turn>) -> Vec<Turn> {
+    if prefix.is_empty() || rest.is_empty() {
+        prefix.extend(rest);
+        return prefix;
+    }
+    if rest[0].number == 0 {
+        // It continues the turn the prefix ends in
+        let first = rest.remove(0);
+        prefix.last_mut().unwrap().turns.extend(first.turns);
+    } else {
+        rest[0].double_dot = false;
+    }
+    prefix.extend(rest);
+    prefix
+}
@@ -1,6 +1,7 @@
 use fen4::{Position, PositionParseError};
 use std::str::FromStr;
 
+use crate::letters::PieceLetters;
 use crate::types::*;
 
 use thiserror::Error;
@@ -103,10 +104,11 @@ impl FromStr for Move {
     }
 }
 
-struct MovePair {
-    main: Move,
-    modifier: Option<Move>,
-    stalemate: bool,
+/// A move together with the resign / timeout that may follow it and the extra 'S'
+pub(crate) struct MovePair {
+    pub(crate) main: Move,
+    pub(crate) modifier: Option<Move>,
+    pub(crate) stalemate: bool,
 }
 
 impl FromStr for MovePair {
@@ -163,7 +165,10 @@ enum IntermediateError {
     Description(usize),
 }
 
-fn parse_quarter(string: &str) -> Result<(QuarterTurn, &str), IntermediateError> {
+fn parse_quarter<'a>(
+    string: &'a str,
+    letters: Option<&PieceLetters>,
+) -> Result<(QuarterTurn, &'a str), IntermediateError> {
     /// Generally the move is bounded by whitespace, but supporting pgns that don't
     /// have all the neccessary whitespace is good. Notably, whitespace before a new
     ///  line number is critical.
@@ -181,8 +186,15 @@ fn parse_quarter(string: &str) -> Result<(QuarterTurn, &str), IntermediateError>
     }
     let split = trimmed.find(next_move).unwrap_or(string.len() - 1);
     let (main_str, mut rest) = trimmed.split_at(split);
-    let move_pair = main_str
-        .trim()
+    let english;
+    let move_str = match letters {
+        Some(letters) => {
+            english = letters.english_move(main_str.trim());
+            english.as_str()
+        }
+        None => main_str.trim(),
+    };
+    let move_pair = move_str
         .parse::<MovePair>()
         .map_err(|m| MoveErr(m, main_str.to_owned(), rest.len()))?;
     let mut description = None;
@@ -217,7 +229,7 @@ fn parse_quarter(string: &str) -> Result<(QuarterTurn, &str), IntermediateError>
         rest = rest_tmp;
         let mut turns = Vec::new();
         while rest.chars().next() != Some(')') {
-            let (turn, rest_tmp) = parse_turn(rest)?;
+            let (turn, rest_tmp) = parse_turn(rest, letters)?;
             rest = rest_tmp;
             turns.push(turn);
         }
@@ -236,7 +248,10 @@ fn parse_quarter(string: &str) -> Result<(QuarterTurn, &str), IntermediateError>
     ))
 }
 
-fn parse_turn(string: &str) -> Result<(Turn, &str), IntermediateError> {
+fn parse_turn<'a>(
+    string: &'a str,
+    letters: Option<&PieceLetters>,
+) -> Result<(Turn, &'a str), IntermediateError> {
     use IntermediateError::*;
     let trimmed = string.trim_start();
     let dot_loc = trimmed.find('.').ok_or(TurnNumber(trimmed.len()))?;
@@ -256,14 +271,14 @@ fn parse_turn(string: &str) -> Result<(Turn, &str), IntermediateError> {
     };
     let mut turns = Vec::new();
     let for_error = rest.len();
-    let (qturn, rest_tmp) = parse_quarter(rest)?;
+    let (qturn, rest_tmp) = parse_quarter(rest, letters)?;
     rest = rest_tmp.trim_start();
     turns.push(qturn);
     while let Some(rest_tmp) = rest.strip_prefix("..") {
         if turns.len() >= 4 {
             return Err(TurnTooLong(for_error));
         }
-        let (qturn, rest_tmp) = parse_quarter(rest_tmp)?;
+        let (qturn, rest_tmp) = parse_quarter(rest_tmp, letters)?;
         rest = rest_tmp.trim_start();
         turns.push(qturn);
     }
@@ -311,35 +326,40 @@ impl std::fmt::Display for ErrorLocation {
 impl FromStr for PGN4 {
     type Err = PGN4Error;
     fn from_str(string: &str) -> Result<Self, Self::Err> {
-        let mut bracketed = Vec::new();
-        let mut rest = string;
-        while let Some(rest_tmp) = rest.strip_prefix('[') {
-            let label_end = rest_tmp.find(|c: char| c.is_whitespace()).unwrap_or(0);
-            let (label, middle) = rest_tmp.split_at(label_end);
-            rest = middle
-                .trim_start()
-                .strip_prefix('"')
-                .ok_or_else(|| make_tagged(rest_tmp, string))?;
+        parse_pgn(string, None)
+    }
+}
 
-            let value_end = rest
-                .find('"')
-                .ok_or_else(|| make_tagged(rest_tmp, string))?;
-            let (value, end) = rest.split_at(value_end);
-            rest = end
-                .strip_prefix("\"]")
-                .ok_or_else(|| make_tagged(rest_tmp, string))?
-                .trim_start();
+/// Parses a whole file, with moves using `letters` for pieces if given
+pub(crate) fn parse_pgn(string: &str, letters: Option<&PieceLetters>) -> Result<PGN4, PGN4Error> {
+    let mut bracketed = Vec::new();
+    let mut rest = string;
+    while let Some(rest_tmp) = rest.strip_prefix('[') {
+        let label_end = rest_tmp.find(|c: char| c.is_whitespace()).unwrap_or(0);
+        let (label, middle) = rest_tmp.split_at(label_end);
+        rest = middle
+            .trim_start()
+            .strip_prefix('"')
+            .ok_or_else(|| make_tagged(rest_tmp, string))?;
 
-            bracketed.push((label.to_owned(), value.to_owned()));
-        }
-        let mut turns = Vec::new();
-        while rest != "" {
-            let (turn, rest_tmp) = parse_turn(rest).map_err(|ie| add_details(ie, string))?;
-            rest = rest_tmp;
-            turns.push(turn);
-        }
-        Ok(PGN4 { bracketed, turns })
+        let value_end = rest
+            .find('"')
+            .ok_or_else(|| make_tagged(rest_tmp, string))?;
+        let (value, end) = rest.split_at(value_end);
+        rest = end
+            .strip_prefix("\"]")
+            .ok_or_else(|| make_tagged(rest_tmp, string))?
+            .trim_start();
+
+        bracketed.push((label.to_owned(), value.to_owned()));
+    }
+    let mut turns = Vec::new();
+    while !rest.is_empty() {
+        let (turn, rest_tmp) = parse_turn(rest, letters).map_err(|ie| add_details(ie, string))?;
+        rest = rest_tmp;
+        turns.push(turn);
     }
+    Ok(PGN4 { bracketed, turns })
 }
 
 fn map_location(bytes_left: usize, base: &str) -> ErrorLocation {
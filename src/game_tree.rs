@@ -0,0 +1,264 @@
+use crate::path::Path;
+use crate::types::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Identifies a move in a [`GameTree`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// Where the node is in [`GameTree::nodes`]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A single move in a [`GameTree`] with links to the moves around it
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Node {
+    pub main: Move,
+    pub modifier: Option<Move>,
+    pub extra_stalemate: bool,
+    pub description: Option<String>,
+    /// If this move is the first of a turn. Turn numbers are counted from these.
+    pub turn_start: bool,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    variation_of: Option<NodeId>,
+}
+
+/// The moves of a game as a tree of nodes stored side by side, an alternative to the nested
+/// [`Turn`]s of [`PGN4`].
+///
+/// Each node knows the move before it and the moves that can follow, so going in any
+/// direction is a lookup. The children of a move are the main continuation followed by its
+/// alternatives. Turn numbers are not stored; they are worked out again by
+/// [`to_pgn4`](GameTree::to_pgn4).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GameTree {
+    pub bracketed: Vec<(String, String)>,
+    nodes: Vec<Node>,
+    /// The moves that can be played from the start
+    roots: Vec<NodeId>,
+}
+
+impl GameTree {
+    /// Builds the tree for a game. Empty turns and alternatives are left out.
+    pub fn from_pgn4(pgn: &PGN4) -> Self {
+        let mut tree = Self {
+            bracketed: pgn.bracketed.clone(),
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+        tree.add_line(&pgn.turns, None, None);
+        tree
+    }
+
+    /// Adds the moves of a line that starts after `parent`. If the line is an alternative,
+    /// `variation_of` is the move its first move replaces.
+    fn add_line(&mut self, turns: &[Turn], parent: Option<NodeId>, variation_of: Option<NodeId>) {
+        let mut previous = parent;
+        let mut first = true;
+        for turn in turns.iter().filter(|t| !t.turns.is_empty()) {
+            for (k, qturn) in turn.turns.iter().enumerate() {
+                // An alternative without a number, or a mainline written as "1..", starts in
+                // the middle of a turn
+                let mid_turn = first
+                    && match variation_of {
+                        Some(_) => turn.number == 0,
+                        None => turn.double_dot,
+                    };
+                let id = NodeId(self.nodes.len());
+                self.nodes.push(Node {
+                    main: qturn.main.clone(),
+                    modifier: qturn.modifier.clone(),
+                    extra_stalemate: qturn.extra_stalemate,
+                    description: qturn.description.clone(),
+                    turn_start: k == 0 && !mid_turn,
+                    parent: previous,
+                    children: Vec::new(),
+                    variation_of: if first { variation_of } else { None },
+                });
+                match previous {
+                    Some(p) => self.nodes[p.0].children.push(id),
+                    None => self.roots.push(id),
+                }
+                for alt in qturn.alternatives.iter().filter(|a| !a.is_empty()) {
+                    self.add_line(alt, previous, Some(id));
+                }
+                previous = Some(id);
+                first = false;
+            }
+        }
+    }
+
+    /// Turns the tree back into a game, numbering the turns the way Chess.com does.
+    ///
+    /// If the first move of the mainline doesn't start a turn, the game starts with `1..`.
+    pub fn to_pgn4(&self) -> PGN4 {
+        PGN4 {
+            bracketed: self.bracketed.clone(),
+            turns: match self.roots.first() {
+                Some(first) => self.line(*first, 0, false),
+                None => Vec::new(),
+            },
+        }
+    }
+
+    /// The turns of the line starting with `first`, where `number` turns came before it
+    fn line(&self, first: NodeId, mut number: usize, alternative: bool) -> Vec<Turn> {
+        let mut turns: Vec<Turn> = Vec::new();
+        let mut next = Some(first);
+        while let Some(id) = next {
+            let node = &self.nodes[id.0];
+            // A mainline starting in the middle of a turn is already in turn 1
+            if turns.is_empty() && !alternative && !node.turn_start {
+                number += 1;
+            }
+            let before = number;
+            if node.turn_start {
+                number += 1;
+            }
+            let qturn = QuarterTurn {
+                main: node.main.clone(),
+                modifier: node.modifier.clone(),
+                extra_stalemate: node.extra_stalemate,
+                description: node.description.clone(),
+                alternatives: self
+                    .alternatives(id)
+                    .map(|alt| self.line(alt, before, true))
+                    .collect(),
+            };
+            if turns.is_empty() {
+                turns.push(Turn {
+                    number: if alternative && !node.turn_start {
+                        0
+                    } else {
+                        number
+                    },
+                    double_dot: alternative || !node.turn_start,
+                    turns: vec![qturn],
+                });
+            } else if node.turn_start {
+                turns.push(Turn {
+                    number,
+                    double_dot: false,
+                    turns: vec![qturn],
+                });
+            } else {
+                turns.last_mut().unwrap().turns.push(qturn);
+            }
+            next = self.main_child(id);
+        }
+        turns
+    }
+
+    /// The first moves of the alternatives to `id`
+    fn alternatives(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.siblings(id)
+            .iter()
+            .copied()
+            .filter(move |s| self.nodes[s.0].variation_of == Some(id))
+    }
+
+    /// `id` and the other moves that can be played instead of it
+    pub fn siblings(&self, id: NodeId) -> &[NodeId] {
+        match self.nodes[id.0].parent {
+            Some(parent) => &self.nodes[parent.0].children,
+            None => &self.roots,
+        }
+    }
+
+    /// Every node; a [`NodeId`] is an index into this
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+    pub fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id.0]
+    }
+    /// The moves that can be played from the start, the first being the mainline
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+    /// The move played before `id`, or `None` if it is played from the start
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].parent
+    }
+    /// The moves that can be played after `id`, the first being the main continuation
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id.0].children
+    }
+    /// The main continuation after `id`
+    pub fn main_child(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].children.first().copied()
+    }
+    /// For the first move of an alternative, the move it is an alternative to
+    pub fn variation_of(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].variation_of
+    }
+
+    /// The path of `id`, as used by [`PGN4`] methods
+    pub fn path(&self, id: NodeId) -> Path {
+        let mut reversed = Vec::new();
+        let mut ply = 0;
+        let mut current = id;
+        loop {
+            ply += 1;
+            let node = &self.nodes[current.0];
+            if let Some(of) = node.variation_of {
+                let alt = self.alternatives(of).position(|a| a == current).unwrap() + 1;
+                reversed.extend(&[ply, alt]);
+                ply = 0;
+                current = of;
+                continue;
+            }
+            match node.parent {
+                Some(parent) => current = parent,
+                None => break,
+            }
+        }
+        reversed.push(ply);
+        reversed.reverse();
+        Path(reversed)
+    }
+
+    /// The node at `path`, or `None` if the path is `[0]` or leads nowhere
+    pub fn node_at(&self, path: &[usize]) -> Option<NodeId> {
+        let mut current: Option<NodeId> = None;
+        for (i, step) in path.iter().enumerate() {
+            if i % 2 == 1 {
+                let of = current?;
+                current = Some(self.alternatives(of).nth(step.checked_sub(1)?)?);
+                continue;
+            }
+            // Inside an alternative, its first move already counts as a step
+            let steps = if i == 0 { *step } else { step.checked_sub(1)? };
+            for _ in 0..steps {
+                current = match current {
+                    Some(id) => Some(self.main_child(id)?),
+                    None => Some(*self.roots.first()?),
+                };
+            }
+        }
+        current
+    }
+}
+
+impl From<&PGN4> for GameTree {
+    fn from(pgn: &PGN4) -> Self {
+        Self::from_pgn4(pgn)
+    }
+}
+
+impl From<&GameTree> for PGN4 {
+    fn from(tree: &GameTree) -> Self {
+        tree.to_pgn4()
+    }
+}
@@ -0,0 +1,64 @@
+use fen4::{Board, Color, Piece, Position};
+
+/// 64 bit Zobrist hash of a board
+///
+/// Covers every square (including walls and dead pieces), the player to move, which
+/// players are out, castling rights, en passant and remaining lives. The move counters and
+/// points are not part of the position and are left out so repetitions hash the same.
+pub fn zobrist(board: &Board) -> u64 {
+    let mut hash = state_key(board);
+    for row in 0..14 {
+        for col in 0..14 {
+            hash ^= square_key(Position { row, col }, &board.board[row][col]);
+        }
+    }
+    hash
+}
+
+/// Key for `piece` standing on `pos`; empty squares don't contribute
+pub(crate) fn square_key(pos: Position, piece: &Piece) -> u64 {
+    let (color, shape) = match piece {
+        Piece::Empty => return 0,
+        Piece::Wall => (15, 0),
+        Piece::Normal(Color::Turn(c), shape) => (usize::from(*c) as u64, *shape as u64),
+        Piece::Normal(Color::Dead(Some(c)), shape) => (4 + usize::from(*c) as u64, *shape as u64),
+        Piece::Normal(Color::Dead(None), shape) => (8, *shape as u64),
+    };
+    key(1, square_index(pos) << 40 | color << 32 | shape)
+}
+
+/// Key for everything about the board other than the squares
+pub(crate) fn state_key(board: &Board) -> u64 {
+    let mut hash = key(2, usize::from(board.turn) as u64);
+    for i in 0..4 {
+        let c = i as u64;
+        if board.dead[i] {
+            hash ^= key(3, c);
+        }
+        if board.castling_king[i] {
+            hash ^= key(4, c << 1);
+        }
+        if board.castling_queen[i] {
+            hash ^= key(4, c << 1 | 1);
+        }
+        if let Some((middle, to)) = board.extra_options.enpassant[i] {
+            hash ^= key(5, c << 32 | square_index(middle) << 16 | square_index(to));
+        }
+        if let Some(lives) = board.extra_options.lives {
+            hash ^= key(6, c << 32 | lives[i] as u64);
+        }
+    }
+    hash
+}
+
+fn square_index(pos: Position) -> u64 {
+    (pos.row * 14 + pos.col) as u64
+}
+
+/// A pseudo-random key for a feature, stable across runs and platforms (SplitMix64)
+fn key(kind: u64, feature: u64) -> u64 {
+    let mut z = (kind << 60 ^ feature).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
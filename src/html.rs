@@ -0,0 +1,193 @@
+use crate::replay::{PositionError, Replay, ReplayError};
+use crate::svg::{escape, piece_color};
+use crate::types::*;
+use fen4::{Color, TurnColor};
+
+/// Choices for writing a game as HTML
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HtmlOptions {
+    /// Write a whole page instead of a fragment that can be put into another page
+    pub standalone: bool,
+    /// The title of the page, if it is standalone
+    pub title: Option<String>,
+    /// Include the script that marks the clicked move and tells other scripts about it
+    pub script: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        Self {
+            standalone: true,
+            title: None,
+            script: true,
+        }
+    }
+}
+
+/// Clicking a move marks it and sends a "pgn4-move" event with its path as the detail
+const SCRIPT: &str = "document.querySelectorAll('.pgn4 .move').forEach(function (move) {
+  move.addEventListener('click', function () {
+    document.querySelectorAll('.pgn4 .move.current').forEach(function (m) {
+      m.classList.remove('current');
+    });
+    move.classList.add('current');
+    move.dispatchEvent(new CustomEvent('pgn4-move', {
+      bubbles: true,
+      detail: move.dataset.path.split(',').map(Number)
+    }));
+  });
+});";
+
+fn class(color: TurnColor) -> &'static str {
+    match color {
+        TurnColor::Red => "red",
+        TurnColor::Blue => "blue",
+        TurnColor::Yellow => "yellow",
+        TurnColor::Green => "green",
+    }
+}
+
+fn style() -> String {
+    let mut css = String::from(
+        ".pgn4 { font-family: sans-serif; line-height: 1.6; }
+.pgn4 .tags th { text-align: left; padding-right: 1em; }
+.pgn4 .number { font-weight: bold; }
+.pgn4 .move { cursor: pointer; padding: 0 2px; border-radius: 3px; }
+.pgn4 .move.current { background: #f7ec59; }
+.pgn4 .comment { color: #666666; font-style: italic; }
+.pgn4 details.variation { margin-left: 1.5em; padding-left: 0.5em; border-left: 2px solid #dddddd; }
+.pgn4 details.variation > summary { cursor: pointer; color: #666666; }
+",
+    );
+    for color in [
+        TurnColor::Red,
+        TurnColor::Blue,
+        TurnColor::Yellow,
+        TurnColor::Green,
+    ] {
+        css.push_str(&format!(
+            ".pgn4 .{} {{ color: {}; }}\n",
+            class(color),
+            piece_color(Color::Turn(color))
+        ));
+    }
+    css
+}
+
+impl PGN4 {
+    /// Writes the game as HTML with every variation in a collapsible block.
+    ///
+    /// Moves and turn numbers are colored by the player making them, so the game is replayed
+    /// and this fails if any line can't be. Each move has its path (as used by
+    /// [`fen4_at`](PGN4::fen4_at)) in a `data-path` attribute like "3,1,2". Nothing is
+    /// loaded from the network.
+    pub fn to_html(&self, options: &HtmlOptions) -> Result<String, PositionError> {
+        let mut moves = String::new();
+        let mut replay = Replay::new(self.variant()?);
+        write_line(&mut moves, &mut replay, &self.turns, &[])?;
+
+        let mut out = String::new();
+        if options.standalone {
+            let title = options.title.as_deref().unwrap_or("Game");
+            out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+            out.push_str(&format!(
+                "<title>{}</title>\n</head>\n<body>\n",
+                escape(title)
+            ));
+        }
+        out.push_str("<div class=\"pgn4\">\n");
+        out.push_str(&format!("<style>\n{}</style>\n", style()));
+        if !self.bracketed.is_empty() {
+            out.push_str("<table class=\"tags\">\n");
+            for (key, value) in &self.bracketed {
+                out.push_str(&format!(
+                    "<tr><th>{}</th><td>{}</td></tr>\n",
+                    escape(key),
+                    escape(value)
+                ));
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("<div class=\"moves\">\n");
+        out.push_str(&moves);
+        out.push_str("</div>\n");
+        if options.script {
+            out.push_str(&format!("<script>\n{}\n</script>\n", SCRIPT));
+        }
+        out.push_str("</div>\n");
+        if options.standalone {
+            out.push_str("</body>\n</html>\n");
+        }
+        Ok(out)
+    }
+}
+
+/// The number at the start of a turn as it is written in a pgn4
+fn number(turn: &Turn) -> String {
+    let number = if turn.number == 0 {
+        String::new()
+    } else {
+        turn.number.to_string()
+    };
+    format!("{}{}", number, if turn.double_dot { ".." } else { "." })
+}
+
+fn write_line(
+    out: &mut String,
+    replay: &mut Replay,
+    turns: &[Turn],
+    path: &[usize],
+) -> Result<(), ReplayError> {
+    let mut ply = 0;
+    for turn in turns {
+        out.push_str("<div class=\"turn\">");
+        for (i, qturn) in turn.turns.iter().enumerate() {
+            ply += 1;
+            let mut move_path = path.to_vec();
+            move_path.push(ply);
+            let color = class(replay.board().turn);
+            if i == 0 {
+                out.push_str(&format!(
+                    "<span class=\"number {}\">{}</span> ",
+                    color,
+                    number(turn)
+                ));
+            } else {
+                out.push_str(" .. ");
+            }
+
+            let text = qturn.move_text();
+            let joined: Vec<String> = move_path.iter().map(|p| p.to_string()).collect();
+            out.push_str(&format!(
+                "<span class=\"move {}\" data-path=\"{}\">{}</span>",
+                color,
+                joined.join(","),
+                escape(&text)
+            ));
+            if let Some(description) = &qturn.description {
+                out.push_str(&format!(
+                    " <span class=\"comment\">{}</span>",
+                    escape(description)
+                ));
+            }
+            for (a, alt) in qturn.alternatives.iter().enumerate() {
+                if alt.is_empty() || alt[0].turns.is_empty() {
+                    continue;
+                }
+                let mut alt_path = move_path.clone();
+                alt_path.push(a + 1);
+                let mut branch = replay.clone();
+                out.push_str(&format!(
+                    "\n<details class=\"variation\" open><summary>{} {}</summary>\n",
+                    number(&alt[0]),
+                    escape(&alt[0].turns[0].move_text())
+                ));
+                write_line(out, &mut branch, alt, &alt_path)?;
+                out.push_str("</details>\n");
+            }
+            replay.play(qturn)?;
+        }
+        out.push_str("</div>\n");
+    }
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use crate::from_str::{MoveError, MovePair};
+use crate::replay::{PositionError, Replay, ReplayError};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+
+use thiserror::Error;
+
+/// A game in a JSON friendly layout that needs no knowledge of [`Turn`]s to use.
+///
+/// ```json
+/// {
+///   "tags": [["Variant", "FFA"]],
+///   "mid_turn": false,
+///   "moves": [
+///     {
+///       "path": [1], "ply": 1, "turn": 1, "color": "R", "move": "h2-h3",
+///       "piece": "P", "from": "h2", "to": "h3", "captured": null, "promotion": null,
+///       "comment": "date=2020-07-17T17:13:12.297Z clock=178312", "clock": 178312,
+///       "variations": [[ { "path": [1, 1, 1], "ply": 1, ... } ]]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Only `tags`, `mid_turn`, `turn`, `move`, `comment` and `variations` are read back by
+/// [`GameJson::to_pgn4`]; the other fields are for convenience. Turn numbers of variations
+/// are written the way Chess.com writes them, so files numbered differently only come back
+/// the same after a round trip through Chess.com.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameJson {
+    /// The tags in the order they are written
+    pub tags: Vec<(String, String)>,
+    /// If the mainline starts in the middle of a turn and is written from "1..", like games
+    /// made by [`PGN4::starting_from`]. Left out, it is `false`.
+    #[serde(default)]
+    pub mid_turn: bool,
+    /// The mainline
+    pub moves: Vec<NodeJson>,
+}
+
+/// A single move of a [`GameJson`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeJson {
+    /// Path to this move as used by [`PGN4::fen4_at`]
+    pub path: Vec<usize>,
+    /// How many moves have been played including this one, counting from the start of the game
+    pub ply: usize,
+    /// The number of the turn this move is in
+    pub turn: usize,
+    /// The player making the move: "R", "B", "Y" or "G"
+    pub color: String,
+    /// The move as written in the pgn4, e.g. "Nb10xQd9+" or "Kh1-i2R"
+    #[serde(rename = "move")]
+    pub text: String,
+    /// The piece moved, if this is a normal move
+    pub piece: Option<char>,
+    /// The square moved from, if this is a normal move
+    pub from: Option<String>,
+    /// The square moved to, if this is a normal move
+    pub to: Option<String>,
+    /// The piece captured
+    pub captured: Option<char>,
+    /// The piece promoted to
+    pub promotion: Option<char>,
+    /// The description of the move
+    pub comment: Option<String>,
+    /// The "clock=" value Chess.com puts in descriptions, in milliseconds
+    pub clock: Option<u64>,
+    /// Lines that could have been played instead of this move
+    pub variations: Vec<Vec<NodeJson>>,
+}
+
+/// Possible errors while reading a [`GameJson`]
+#[derive(Error, Debug)]
+pub enum JsonError {
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Move \"{1}\" at {2:?} failed to parse. {0}")]
+    BadMove(MoveError, String, Vec<usize>),
+    #[error("The variation at {0:?} has no moves")]
+    EmptyVariation(Vec<usize>),
+}
+
+impl PGN4 {
+    /// Converts the game to the [`GameJson`] layout.
+    ///
+    /// The colors of moves come from replaying the game, so this fails if any line can't be.
+    pub fn to_json_game(&self) -> Result<GameJson, PositionError> {
+        let mut replay = Replay::new(self.variant()?);
+        Ok(GameJson {
+            tags: self.bracketed.clone(),
+            mid_turn: matches!(self.turns.first(), Some(t) if t.double_dot),
+            moves: export_line(&mut replay, &self.turns, &[], 0, 1)?,
+        })
+    }
+    /// Writes the game as [`GameJson`]
+    pub fn to_json(&self) -> Result<String, PositionError> {
+        Ok(serde_json::to_string(&self.to_json_game()?).expect("GameJson is always valid JSON"))
+    }
+    /// Reads a game written by [`PGN4::to_json`]
+    pub fn from_json(json: &str) -> Result<Self, JsonError> {
+        serde_json::from_str::<GameJson>(json)?.to_pgn4()
+    }
+}
+
+impl GameJson {
+    /// Converts back to a [`PGN4`]
+    pub fn to_pgn4(&self) -> Result<PGN4, JsonError> {
+        Ok(PGN4 {
+            bracketed: self.tags.clone(),
+            turns: import_line(&self.moves, true, !self.mid_turn)?,
+        })
+    }
+}
+
+/// `ply` and `turn` are for the move the line replaces, or the start of the game
+fn export_line(
+    replay: &mut Replay,
+    turns: &[Turn],
+    path: &[usize],
+    ply: usize,
+    turn_number: usize,
+) -> Result<Vec<NodeJson>, ReplayError> {
+    let mut nodes = Vec::new();
+    for (i, turn) in turns.iter().enumerate() {
+        let number = if turn.number != 0 {
+            turn.number
+        } else {
+            turn_number + i
+        };
+        for qturn in &turn.turns {
+            let index = nodes.len() + 1;
+            let mut node_path = path.to_vec();
+            node_path.push(index);
+
+            let mut variations = Vec::new();
+            for (a, alt) in qturn.alternatives.iter().enumerate() {
+                let mut alt_path = node_path.clone();
+                alt_path.push(a + 1);
+                let mut branch = replay.clone();
+                variations.push(export_line(
+                    &mut branch,
+                    alt,
+                    &alt_path,
+                    ply + index - 1,
+                    number,
+                )?);
+            }
+
+            let text = qturn.move_text();
+            let basic = match &qturn.main {
+                Move::Normal(bm) => Some(bm),
+                _ => None,
+            };
+            nodes.push(NodeJson {
+                path: node_path,
+                ply: ply + index,
+                turn: number,
+                color: replay.board().turn.to_string(),
+                text,
+                piece: basic.map(|bm| bm.piece),
+                from: basic.map(|bm| bm.from.to_string()),
+                to: basic.map(|bm| bm.to.to_string()),
+                captured: basic.and_then(|bm| bm.captured),
+                promotion: basic.and_then(|bm| bm.promotion),
+                comment: qturn.description.clone(),
+                clock: qturn.description.as_deref().and_then(clock),
+                variations,
+            });
+            replay.play(qturn)?;
+        }
+    }
+    Ok(nodes)
+}
+
+/// Reads the "clock=123" part of a description
+fn clock(description: &str) -> Option<u64> {
+    let start = description.find("clock=")? + "clock=".len();
+    let digits: String = description[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+/// Turns are numbered like [`Display`](std::fmt::Display) expects: variations that start
+/// a turn show its number with "..", ones that start in the middle of a turn show none. A
+/// mainline starting in the middle of a turn shows its number with "..".
+fn import_line(
+    nodes: &[NodeJson],
+    mainline: bool,
+    starts_turn: bool,
+) -> Result<Vec<Turn>, JsonError> {
+    let mut turns: Vec<Turn> = Vec::new();
+    let mut previous = None;
+    for node in nodes {
+        // Whether this move is the first of its turn
+        let new_turn = match previous {
+            None => starts_turn,
+            Some(turn) => turn != node.turn,
+        };
+        if previous.is_none() {
+            turns.push(Turn {
+                number: if starts_turn || mainline {
+                    node.turn
+                } else {
+                    0
+                },
+                double_dot: !mainline || !starts_turn,
+                turns: Vec::new(),
+            });
+        } else if new_turn {
+            turns.push(Turn {
+                number: node.turn,
+                double_dot: false,
+                turns: Vec::new(),
+            });
+        }
+        previous = Some(node.turn);
+
+        let pair = node
+            .text
+            .parse::<MovePair>()
+            .map_err(|e| JsonError::BadMove(e, node.text.clone(), node.path.clone()))?;
+        let mut alternatives = Vec::new();
+        for (i, variation) in node.variations.iter().enumerate() {
+            if variation.is_empty() {
+                let mut path = node.path.clone();
+                path.push(i + 1);
+                return Err(JsonError::EmptyVariation(path));
+            }
+            alternatives.push(import_line(variation, false, new_turn)?);
+        }
+        turns.last_mut().unwrap().turns.push(QuarterTurn {
+            main: pair.main,
+            modifier: pair.modifier,
+            extra_stalemate: pair.stalemate,
+            description: node.comment.clone(),
+            alternatives,
+        });
+    }
+    Ok(turns)
+}
@@ -0,0 +1,119 @@
+use crate::from_str::{parse_pgn, PGN4Error};
+use crate::types::*;
+use crate::writer::{WriteOptions, Writer};
+
+use std::fmt;
+
+/// The letters pieces are written with, for writing and reading moves in other languages
+///
+/// The default is the English letters used by PGN4 files (P, N, B, R, Q, K and D for the
+/// queen a pawn promotes to in FFA). Only the piece letters of moves change; the letters
+/// for resigning, timing out and stalemate ("R", "T" and "S") and castling stay the same.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PieceLetters {
+    /// Pairs of (English letter, letter to use instead)
+    letters: Vec<(char, char)>,
+}
+
+impl PieceLetters {
+    /// The standard letters
+    pub fn english() -> Self {
+        Self::default()
+    }
+    /// Unicode chess figurines. The queen a pawn promotes to ('D') is written as a black queen.
+    pub fn figurines() -> Self {
+        Self::new(&[
+            ('K', '♔'),
+            ('Q', '♕'),
+            ('R', '♖'),
+            ('B', '♗'),
+            ('N', '♘'),
+            ('P', '♙'),
+            ('D', '♛'),
+        ])
+    }
+    /// Replaces each English letter with another letter, e.g. `[('N', 'S'), ('R', 'T')]` for
+    /// some of the German letters. Letters that are not listed stay the same.
+    ///
+    /// Moves can only be read back if no two pieces end up with the same letter.
+    pub fn new(letters: &[(char, char)]) -> Self {
+        Self {
+            letters: letters.to_vec(),
+        }
+    }
+    /// The letter to write for the English letter `piece`
+    pub fn localize(&self, piece: char) -> char {
+        self.letters
+            .iter()
+            .find(|(english, _)| *english == piece)
+            .map_or(piece, |(_, local)| *local)
+    }
+    /// The English letter for the localized letter `piece`
+    pub fn english_of(&self, piece: char) -> char {
+        self.letters
+            .iter()
+            .find(|(_, local)| *local == piece)
+            .map_or(piece, |(english, _)| *english)
+    }
+    /// Writes a move with these letters
+    pub fn write_move(&self, m: &Move) -> String {
+        self.localize_move(&m.to_string())
+    }
+    /// Changes the piece letters of a written move (long or short notation) to these letters
+    pub fn localize_move(&self, text: &str) -> String {
+        translate(text, |c| self.localize(c))
+    }
+    /// Changes the piece letters of a move written with these letters back to English, so
+    /// it can be parsed
+    pub fn english_move(&self, text: &str) -> String {
+        translate(text, |c| self.english_of(c))
+    }
+}
+
+/// Maps the letters of a written move that name pieces: the first letter, and the letters
+/// after an 'x' or '=' unless they are the file of a square.
+fn translate(text: &str, map: impl Fn(char) -> char) -> String {
+    // The shortest moves are single letters or pairs like "RS", which are never pieces
+    if text.chars().count() <= 2 || text.starts_with("O-O") {
+        return text.to_owned();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut previous = None;
+    for c in text.chars() {
+        let piece = matches!(previous, None | Some('x') | Some('='));
+        out.push(if piece && !c.is_ascii_lowercase() {
+            map(c)
+        } else {
+            c
+        });
+        previous = Some(c);
+    }
+    out
+}
+
+/// Displays a game with localized piece letters; made by [`PGN4::with_letters`]
+pub struct Localized<'a> {
+    pgn: &'a PGN4,
+    letters: &'a PieceLetters,
+}
+
+impl PGN4 {
+    /// Parses a file written with other piece letters
+    pub fn parse_with_letters(string: &str, letters: &PieceLetters) -> Result<Self, PGN4Error> {
+        parse_pgn(string, Some(letters))
+    }
+    /// Writes the game with other piece letters
+    pub fn with_letters<'a>(&'a self, letters: &'a PieceLetters) -> Localized<'a> {
+        Localized { pgn: self, letters }
+    }
+}
+
+impl fmt::Display for Localized<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = WriteOptions {
+            letters: self.letters.clone(),
+            ..WriteOptions::chess_com()
+        };
+        Writer::new(f, &options).pgn(self.pgn, None)
+    }
+}
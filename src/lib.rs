@@ -1,9 +1,54 @@
 mod display;
+mod draws;
+mod flatten;
 mod from;
 mod from_str;
+mod game_tree;
+mod hash;
+mod html;
+#[cfg(feature = "json")]
+mod json;
+mod letters;
+mod movegen;
+mod path;
+mod perft;
+#[cfg(feature = "raster")]
+mod raster;
+mod render;
+mod replay;
+mod rules;
+#[cfg(feature = "serde")]
+mod serialize;
+mod short;
+mod svg;
 mod traits;
+mod tree;
 mod types;
 mod visitor;
+mod walker;
+mod writer;
+pub use draws::*;
+pub use flatten::*;
 pub use from_str::PGN4Error;
+pub use game_tree::*;
+pub use hash::zobrist;
+pub use html::*;
+#[cfg(feature = "json")]
+pub use json::*;
+pub use letters::*;
+pub use movegen::*;
+pub use path::*;
+pub use perft::*;
+#[cfg(feature = "raster")]
+pub use raster::*;
+pub use render::*;
+pub use replay::*;
+pub use rules::*;
+pub use short::*;
+pub use svg::*;
+pub use traits::{EditError, VariantError};
+pub use tree::*;
 pub use types::*;
 pub use visitor::*;
+pub use walker::*;
+pub use writer::WriteOptions;
@@ -0,0 +1,533 @@
+use crate::types::*;
+use fen4::{Board, Color, Piece, Position, TurnColor};
+
+/// The raw grid of a [`Board`]; indexed `[row][col]`
+pub(crate) type Squares = [[Piece; 14]; 14];
+
+/// All four colors in turn order
+pub(crate) const COLORS: [TurnColor; 4] = [
+    TurnColor::Red,
+    TurnColor::Blue,
+    TurnColor::Yellow,
+    TurnColor::Green,
+];
+
+const KNIGHT: [(isize, isize); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const ORTHOGONAL: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const DIAGONAL: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const ROYAL: [(isize, isize); 8] = [
+    (1, 0),
+    (0, 1),
+    (-1, 0),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+/// Is (row, col) on the 14x14 board with the 3x3 corners cut out
+pub(crate) fn on_board(row: isize, col: isize) -> bool {
+    let edge = |x: isize| !(3..=10).contains(&x);
+    (0..14).contains(&row) && (0..14).contains(&col) && !(edge(row) && edge(col))
+}
+
+fn offset(pos: Position, (dr, dc): (isize, isize)) -> Option<Position> {
+    let row = pos.row as isize + dr;
+    let col = pos.col as isize + dc;
+    if on_board(row, col) {
+        Some(Position {
+            row: row as usize,
+            col: col as usize,
+        })
+    } else {
+        None
+    }
+}
+
+fn at(squares: &Squares, pos: Position) -> &Piece {
+    &squares[pos.row][pos.col]
+}
+
+/// The direction pawns of a color move in as (row, col)
+pub(crate) fn forward(color: TurnColor) -> (isize, isize) {
+    match color {
+        TurnColor::Red => (1, 0),
+        TurnColor::Blue => (0, 1),
+        TurnColor::Yellow => (-1, 0),
+        TurnColor::Green => (0, -1),
+    }
+}
+
+/// Rank of a square counted from the edge the color starts on (1-14)
+pub(crate) fn rank_for(color: TurnColor, pos: Position) -> usize {
+    match color {
+        TurnColor::Red => pos.row + 1,
+        TurnColor::Blue => pos.col + 1,
+        TurnColor::Yellow => 14 - pos.row,
+        TurnColor::Green => 14 - pos.col,
+    }
+}
+
+/// Square `i` (0-7) of the back rank of a color, ordered like the files a-h in chess
+pub(crate) fn back_rank(color: TurnColor, i: usize) -> Position {
+    let (row, col) = match color {
+        TurnColor::Red => (0, i + 3),
+        TurnColor::Blue => (i + 3, 0),
+        TurnColor::Yellow => (13, 10 - i),
+        TurnColor::Green => (10 - i, 13),
+    };
+    Position { row, col }
+}
+
+/// Inverse of [`back_rank`]
+pub(crate) fn back_rank_index(color: TurnColor, pos: Position) -> Option<usize> {
+    (0..8).find(|i| back_rank(color, *i) == pos)
+}
+
+/// Can pieces of color `a` capture or check pieces of color `b`
+pub(crate) fn opponents(variant: &Variant, a: TurnColor, b: TurnColor) -> bool {
+    a != b && variant.teammate(a) != Some(b)
+}
+
+/// Squares of the pieces a color needs to keep out of check
+///
+/// This is the `royal` square in the fen4 metadata if there is one and every king otherwise.
+/// Only squares that still hold a living piece of that color are returned.
+pub fn royal_squares(board: &Board, color: TurnColor) -> Vec<Position> {
+    if let Some(p) = board.extra_options.royal[usize::from(color)] {
+        return match board.board[p.row][p.col] {
+            Piece::Normal(Color::Turn(c), _) if c == color => vec![p],
+            _ => Vec::new(),
+        };
+    }
+    let mut out = Vec::new();
+    for row in 0..14 {
+        for col in 0..14 {
+            if board.board[row][col] == Piece::Normal(Color::Turn(color), 'K') {
+                out.push(Position { row, col });
+            }
+        }
+    }
+    out
+}
+
+/// Could a living opponent of `color` capture on `pos`
+pub fn is_attacked(board: &Board, variant: &Variant, pos: Position, color: TurnColor) -> bool {
+    attacked(&board.board, pos, |c| opponents(variant, c, color))
+}
+
+/// Could a living piece of a color matching `by` capture on `pos`
+fn attacked(squares: &Squares, pos: Position, by: impl Fn(TurnColor) -> bool) -> bool {
+    let enemy = |piece: &Piece, shapes: &[char]| match piece {
+        Piece::Normal(Color::Turn(c), shape) => shapes.contains(shape) && by(*c),
+        _ => false,
+    };
+    for delta in &KNIGHT {
+        if let Some(p) = offset(pos, *delta) {
+            if enemy(at(squares, p), &['N']) {
+                return true;
+            }
+        }
+    }
+    for (directions, sliders) in &[(ORTHOGONAL, ['R', 'Q', 'D']), (DIAGONAL, ['B', 'Q', 'D'])] {
+        for delta in directions {
+            let mut current = pos;
+            let mut first = true;
+            while let Some(p) = offset(current, *delta) {
+                let piece = at(squares, p);
+                if piece.is_empty() {
+                    current = p;
+                    first = false;
+                    continue;
+                }
+                if enemy(piece, sliders) || (first && enemy(piece, &['K'])) {
+                    return true;
+                }
+                break;
+            }
+        }
+    }
+    for c in &COLORS {
+        if !by(*c) {
+            continue;
+        }
+        let (fr, fc) = forward(*c);
+        for side in &[-1, 1] {
+            // A pawn attacks pos if pos is one step forward and one to the side of it
+            if let Some(p) = offset(pos, (-fr - fc * side, -fc - fr * side)) {
+                if *at(squares, p) == Piece::Normal(Color::Turn(*c), 'P') {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Do kings have to stay out of check. They do not in "Antichess" or "CaptureTheKing".
+pub(crate) fn royal_rules(variant: &Variant) -> bool {
+    !variant.antichess && !variant.capture_the_king
+}
+
+/// Is any royal piece of `color` attacked
+pub fn in_check(board: &Board, variant: &Variant, color: TurnColor) -> bool {
+    let royals = royal_squares(board, color);
+    royals
+        .iter()
+        .any(|p| attacked(&board.board, *p, |c| opponents(variant, c, color)))
+}
+
+/// Is any royal piece of `color` attacked by a piece of `by`
+pub fn checked_by(board: &Board, variant: &Variant, color: TurnColor, by: TurnColor) -> bool {
+    opponents(variant, by, color)
+        && royal_squares(board, color)
+            .iter()
+            .any(|p| attacked(&board.board, *p, |c| c == by))
+}
+
+/// Can a piece of `color` move onto a square containing `piece` (possibly by capturing)
+///
+/// With "DeadWall", dead pieces can not be captured.
+fn can_enter(variant: &Variant, piece: &Piece, color: TurnColor) -> Option<Option<char>> {
+    match piece {
+        Piece::Empty => Some(None),
+        Piece::Wall => None,
+        Piece::Normal(Color::Dead(_), shape) => {
+            if variant.dead_wall {
+                None
+            } else {
+                Some(Some(*shape))
+            }
+        }
+        Piece::Normal(Color::Turn(c), shape) => {
+            if opponents(variant, color, *c) {
+                Some(Some(*shape))
+            } else {
+                None
+            }
+        }
+    }
+}
+
+fn basic(piece: char, from: Position, captured: Option<char>, to: Position) -> BasicMove {
+    BasicMove {
+        piece,
+        from,
+        captured,
+        to,
+        promotion: None,
+        checks: 0,
+        mates: 0,
+    }
+}
+
+fn pawn_moves(
+    board: &Board,
+    variant: &Variant,
+    from: Position,
+    color: TurnColor,
+) -> Vec<BasicMove> {
+    let squares = &board.board;
+    let mut out = Vec::new();
+    let (fr, fc) = forward(color);
+    if let Some(one) = offset(from, (fr, fc)) {
+        if at(squares, one).is_empty() {
+            out.push(basic('P', from, None, one));
+            let base = board.extra_options.pawnbaserank;
+            if base != 0 && rank_for(color, from) == base {
+                if let Some(two) = offset(one, (fr, fc)) {
+                    if at(squares, two).is_empty() {
+                        out.push(basic('P', from, None, two));
+                    }
+                }
+            }
+        }
+    }
+    for side in &[-1, 1] {
+        if let Some(to) = offset(from, (fr + fc * side, fc + fr * side)) {
+            let target = at(squares, to);
+            if target.is_piece() {
+                if let Some(Some(captured)) = can_enter(variant, target, color) {
+                    out.push(basic('P', from, Some(captured), to));
+                }
+            } else if target.is_empty() && en_passant_target(board, variant, to, color).is_some() {
+                out.push(basic('P', from, Some('P'), to));
+            }
+        }
+    }
+    let mut promoted = Vec::new();
+    for m in out {
+        if rank_for(color, m.to) == variant.pawn_promotion_rank {
+            for p in &variant.promote_to {
+                promoted.push(BasicMove {
+                    promotion: Some(*p),
+                    ..m.clone()
+                });
+            }
+        } else {
+            promoted.push(m);
+        }
+    }
+    promoted
+}
+
+/// If a pawn of `color` moving onto the empty square `to` would capture en passant, the square of the captured pawn
+pub(crate) fn en_passant_target(
+    board: &Board,
+    variant: &Variant,
+    to: Position,
+    color: TurnColor,
+) -> Option<Position> {
+    if !variant.en_passant {
+        return None;
+    }
+    for c in &COLORS {
+        if let Some((capture, pawn)) = board.extra_options.enpassant[usize::from(*c)] {
+            if capture == to
+                && opponents(variant, color, *c)
+                && *at(&board.board, pawn) == Piece::Normal(Color::Turn(*c), 'P')
+            {
+                return Some(pawn);
+            }
+        }
+    }
+    None
+}
+
+fn piece_moves(
+    board: &Board,
+    variant: &Variant,
+    from: Position,
+    color: TurnColor,
+    shape: char,
+) -> Vec<BasicMove> {
+    let squares = &board.board;
+    let (steps, slide): (&[(isize, isize)], bool) = match shape {
+        'P' => return pawn_moves(board, variant, from, color),
+        'N' => (&KNIGHT, false),
+        'B' => (&DIAGONAL, true),
+        'R' => (&ORTHOGONAL, true),
+        'Q' | 'D' => (&ROYAL, true),
+        'K' => (&ROYAL, false),
+        _ => return Vec::new(),
+    };
+    let mut out = Vec::new();
+    for delta in steps {
+        let mut current = from;
+        while let Some(to) = offset(current, *delta) {
+            match can_enter(variant, at(squares, to), color) {
+                Some(captured) => {
+                    out.push(basic(shape, from, captured, to));
+                    if captured.is_some() || !slide {
+                        break;
+                    }
+                }
+                None => break,
+            }
+            current = to;
+        }
+    }
+    out
+}
+
+/// King and rook movement for castling as (king from, king to, rook from, rook to)
+///
+/// The king and rook end on the same squares as in standard chess (and Chess960) so
+/// this works for any back rank setup.
+pub(crate) fn castle_squares(
+    board: &Board,
+    color: TurnColor,
+    kingside: bool,
+) -> Option<(Position, Position, Position, Position)> {
+    let squares = &board.board;
+    let king_i = (0..8)
+        .find(|i| *at(squares, back_rank(color, *i)) == Piece::Normal(Color::Turn(color), 'K'))?;
+    let mut rook_i = None;
+    let mut i = king_i;
+    while (kingside && i < 7) || (!kingside && i > 0) {
+        i = if kingside { i + 1 } else { i - 1 };
+        if *at(squares, back_rank(color, i)) == Piece::Normal(Color::Turn(color), 'R') {
+            rook_i = Some(i);
+            break;
+        }
+    }
+    let rook_i = rook_i?;
+    let (king_to, rook_to) = if kingside { (6, 5) } else { (2, 3) };
+    Some((
+        back_rank(color, king_i),
+        back_rank(color, king_to),
+        back_rank(color, rook_i),
+        back_rank(color, rook_to),
+    ))
+}
+
+fn can_castle(board: &Board, variant: &Variant, color: TurnColor, kingside: bool) -> bool {
+    let index = usize::from(color);
+    let rights = if kingside {
+        board.castling_king[index]
+    } else {
+        board.castling_queen[index]
+    };
+    if !rights {
+        return false;
+    }
+    let (king_from, king_to, rook_from, rook_to) = match castle_squares(board, color, kingside) {
+        Some(s) => s,
+        None => return false,
+    };
+    let index_of = |p| back_rank_index(color, p).unwrap();
+    let all = [
+        index_of(king_from),
+        index_of(king_to),
+        index_of(rook_from),
+        index_of(rook_to),
+    ];
+    let low = *all.iter().min().unwrap();
+    let high = *all.iter().max().unwrap();
+    for i in low..=high {
+        let p = back_rank(color, i);
+        if p != king_from && p != rook_from && !at(&board.board, p).is_empty() {
+            return false;
+        }
+    }
+    let (king_low, king_high) = if index_of(king_from) < index_of(king_to) {
+        (index_of(king_from), index_of(king_to))
+    } else {
+        (index_of(king_to), index_of(king_from))
+    };
+    // The rook is gone from its square when the king passes over it
+    let mut squares = board.board.clone();
+    squares[rook_from.row][rook_from.col] = Piece::Empty;
+    (king_low..=king_high).all(|i| {
+        !attacked(&squares, back_rank(color, i), |c| {
+            opponents(variant, c, color)
+        })
+    })
+}
+
+/// All moves for `color` that follow how the pieces move, ignoring whether they leave a king in check
+pub fn pseudo_legal_moves(board: &Board, variant: &Variant, color: TurnColor) -> Vec<Move> {
+    let mut out = Vec::new();
+    for row in 0..14 {
+        for col in 0..14 {
+            if let Piece::Normal(Color::Turn(c), shape) = board.board[row][col] {
+                if c == color {
+                    let from = Position { row, col };
+                    for m in piece_moves(board, variant, from, color, shape) {
+                        out.push(Move::Normal(m));
+                    }
+                }
+            }
+        }
+    }
+    if can_castle(board, variant, color, true) {
+        out.push(Move::KingCastle(0));
+    }
+    if can_castle(board, variant, color, false) {
+        out.push(Move::QueenCastle(0));
+    }
+    out
+}
+
+/// Moves the `royal` square of `color` along with its piece, castling included.
+///
+/// Must be called before [`move_pieces`], while the king is still on its square.
+pub(crate) fn move_royal(board: &mut Board, color: TurnColor, m: &Move) {
+    let (from, to) = match m {
+        Move::Normal(bm) => (bm.from, bm.to),
+        Move::KingCastle(_) | Move::QueenCastle(_) => {
+            let kingside = matches!(m, Move::KingCastle(_));
+            match castle_squares(board, color, kingside) {
+                Some((king_from, king_to, _, _)) => (king_from, king_to),
+                None => return,
+            }
+        }
+        _ => return,
+    };
+    let royal = &mut board.extra_options.royal[usize::from(color)];
+    if *royal == Some(from) {
+        *royal = Some(to);
+    }
+}
+
+/// Moves pieces on the grid without any other bookkeeping.
+///
+/// Returns the square a piece was captured from, if any.
+pub(crate) fn move_pieces(
+    board: &mut Board,
+    variant: &Variant,
+    color: TurnColor,
+    m: &Move,
+) -> Option<Position> {
+    match m {
+        Move::KingCastle(_) | Move::QueenCastle(_) => {
+            let kingside = matches!(m, Move::KingCastle(_));
+            if let Some((king_from, king_to, rook_from, rook_to)) =
+                castle_squares(board, color, kingside)
+            {
+                let squares = &mut board.board;
+                let king = std::mem::take(&mut squares[king_from.row][king_from.col]);
+                let rook = std::mem::take(&mut squares[rook_from.row][rook_from.col]);
+                squares[king_to.row][king_to.col] = king;
+                squares[rook_to.row][rook_to.col] = rook;
+            }
+            None
+        }
+        Move::Normal(bm) => {
+            let mut captured_at = None;
+            if bm.piece == 'P' && board.board[bm.to.row][bm.to.col].is_empty() {
+                if let Some(pawn) = en_passant_target(board, variant, bm.to, color) {
+                    board.board[pawn.row][pawn.col] = Piece::Empty;
+                    captured_at = Some(pawn);
+                }
+            }
+            let squares = &mut board.board;
+            let mut piece = std::mem::take(&mut squares[bm.from.row][bm.from.col]);
+            if let (Some(p), Piece::Normal(c, _)) = (bm.promotion, &piece) {
+                piece = Piece::Normal(*c, p);
+            }
+            if squares[bm.to.row][bm.to.col].is_piece() {
+                captured_at = Some(bm.to);
+            }
+            squares[bm.to.row][bm.to.col] = piece;
+            captured_at
+        }
+        _ => None,
+    }
+}
+
+/// All legal moves for `color`
+///
+/// In "Antichess", only captures are legal if there are any.
+/// `checks` and `mates` are not filled in on the returned moves.
+pub fn legal_moves_for(board: &Board, variant: &Variant, color: TurnColor) -> Vec<Move> {
+    let mut moves = pseudo_legal_moves(board, variant, color);
+    if royal_rules(variant) {
+        moves.retain(|m| {
+            let mut after = board.clone();
+            move_royal(&mut after, color, m);
+            move_pieces(&mut after, variant, color, m);
+            !in_check(&after, variant, color)
+        });
+    }
+    let is_capture = |m: &Move| matches!(m, Move::Normal(bm) if bm.captured.is_some());
+    if variant.antichess && moves.iter().any(is_capture) {
+        moves.retain(is_capture);
+    }
+    moves
+}
+
+/// All legal moves for the player whose turn it is
+pub fn legal_moves(board: &Board, variant: &Variant) -> Vec<Move> {
+    legal_moves_for(board, variant, board.turn)
+}
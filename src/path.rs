@@ -0,0 +1,180 @@
+use crate::visitor::VisitingError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A place in a game: the position after a move, or `[0]` for the start.
+///
+/// The format is the one Chess.com uses: `[ply] : [alternative, ply]*`. Follow the line for
+/// `ply` moves, then if there is more, go into the 1-based `alternative` of that move (which
+/// replaces it) and continue with the rest. So a path always has an odd length, and zero
+/// only appears in `[0]`.
+///
+/// It is written with dashes, like "3-1-2".
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(try_from = "Vec<usize>", into = "Vec<usize>")
+)]
+pub struct Path(pub(crate) Vec<usize>);
+
+/// Possible errors while reading a [`Path`]
+#[derive(Error, PartialEq, Debug, Clone)]
+pub enum PathError {
+    #[error("\"{0}\" is not a number")]
+    NotANumber(String),
+    #[error("Invalid path: {0}")]
+    Invalid(#[from] VisitingError),
+}
+
+impl Path {
+    /// The start of the game, `[0]`
+    pub fn start() -> Self {
+        Self(vec![0])
+    }
+    /// After `ply` moves of the mainline
+    pub fn mainline(ply: usize) -> Self {
+        Self(vec![ply])
+    }
+    /// Checks that `path` follows the rules above
+    pub fn new(path: Vec<usize>) -> Result<Self, VisitingError> {
+        check(&path)?;
+        Ok(Self(path))
+    }
+    pub fn as_slice(&self) -> &[usize] {
+        &self.0
+    }
+    pub fn into_vec(self) -> Vec<usize> {
+        self.0
+    }
+    pub fn is_start(&self) -> bool {
+        self.0 == [0]
+    }
+    /// How many variations deep the path goes
+    pub fn depth(&self) -> usize {
+        self.0.len() / 2
+    }
+    /// How far into its line the path goes
+    pub fn ply(&self) -> usize {
+        self.0[self.0.len() - 1]
+    }
+    /// Moves one move further along the line
+    pub fn push_ply(&mut self) {
+        let last = self.0.len() - 1;
+        self.0[last] += 1;
+    }
+    /// Moves back one move, leaving a variation if this is its first move.
+    ///
+    /// Returns false if this is the start of the game already.
+    pub fn pop_ply(&mut self) -> bool {
+        if self.is_start() {
+            return false;
+        }
+        loop {
+            let last = self.0.len() - 1;
+            if self.0[last] > 1 {
+                self.0[last] -= 1;
+                return true;
+            }
+            if last == 0 {
+                self.0[0] = 0;
+                return true;
+            }
+            // Before the first move of a variation is before the move it replaces
+            self.0.truncate(last - 1);
+        }
+    }
+    /// Goes to the first move of the 1-based `alternative` of the move this points at
+    pub fn push_variation(&mut self, alternative: usize) -> Result<(), VisitingError> {
+        if alternative == 0 || self.is_start() {
+            return Err(VisitingError::InvalidAlternative);
+        }
+        self.0.extend(&[alternative, 1]);
+        Ok(())
+    }
+    /// Leaves the current variation, going to the move it is an alternative to.
+    ///
+    /// Returns which alternative it was, or `None` if this is the mainline.
+    pub fn pop_variation(&mut self) -> Option<usize> {
+        if self.0.len() < 3 {
+            return None;
+        }
+        self.0.pop();
+        self.0.pop()
+    }
+}
+
+/// The rules every path has to follow
+pub(crate) fn check(path: &[usize]) -> Result<(), VisitingError> {
+    if path.len() % 2 == 0 {
+        return Err(VisitingError::EvenPath);
+    }
+    if path != [0] && path.contains(&0) {
+        return Err(VisitingError::ZeroInPath);
+    }
+    Ok(())
+}
+
+impl Deref for Path {
+    type Target = [usize];
+    fn deref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl AsRef<[usize]> for Path {
+    fn as_ref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl TryFrom<Vec<usize>> for Path {
+    type Error = VisitingError;
+    fn try_from(path: Vec<usize>) -> Result<Self, Self::Error> {
+        Self::new(path)
+    }
+}
+
+impl TryFrom<&[usize]> for Path {
+    type Error = VisitingError;
+    fn try_from(path: &[usize]) -> Result<Self, Self::Error> {
+        Self::new(path.to_vec())
+    }
+}
+
+impl From<Path> for Vec<usize> {
+    fn from(path: Path) -> Self {
+        path.0
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0[0])?;
+        for i in &self.0[1..] {
+            write!(f, "-{}", i)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Path {
+    type Err = PathError;
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let path = string
+            .split('-')
+            .map(|part| {
+                part.trim()
+                    .parse()
+                    .map_err(|_| PathError::NotANumber(part.to_owned()))
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+        Ok(Self::new(path)?)
+    }
+}
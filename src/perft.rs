@@ -0,0 +1,93 @@
+use crate::movegen::*;
+use crate::replay::Replay;
+use crate::rules::*;
+use crate::types::*;
+
+/// Counts the lines of play `depth` quarter turns deep from the position of `replay`.
+///
+/// Only moves count as quarter turns; checkmates and stalemates that can't be avoided are
+/// played as part of the move causing them. Lines where the game ends early are not counted.
+pub fn perft(replay: &Replay, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = replay.legal_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves
+        .iter()
+        .filter_map(|m| play_legal(replay, m))
+        .map(|next| perft(&next, depth - 1))
+        .sum()
+}
+
+/// [`perft`] split up by the first move
+pub fn divide(replay: &Replay, depth: usize) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    replay
+        .legal_moves()
+        .into_iter()
+        .map(|m| {
+            let count = if depth == 1 {
+                1
+            } else {
+                play_legal(replay, &m).map_or(0, |next| perft(&next, depth - 1))
+            };
+            (m, count)
+        })
+        .collect()
+}
+
+/// Plays a legal move, including the checkmates it causes and any players that are stuck
+/// afterwards. Returns `None` if the game is over after that.
+fn play_legal(replay: &Replay, m: &Move) -> Option<Replay> {
+    let mover = replay.board().turn;
+    let mut plain = replay.clone();
+    plain.play(&m.clone().into()).ok()?;
+    let board = plain.board();
+    let variant = plain.variant();
+    let mates = COLORS
+        .iter()
+        .filter(|c| **c != mover && in_play(board, variant, **c))
+        .filter(|c| stuck(board, variant, **c, true))
+        .count();
+
+    let mut next = replay.clone();
+    let events = if mates == 0 {
+        next.play(&m.clone().into())
+    } else {
+        next.play(&with_mates(m, mates).into())
+    }
+    .ok()?;
+    let mut over = events.iter().any(|e| matches!(e, GameEvent::GameOver(_)));
+    while !over && next.legal_moves().is_empty() {
+        let color = next.board().turn;
+        let end = if royal_rules(next.variant()) && in_check(next.board(), next.variant(), color) {
+            Move::Checkmate
+        } else {
+            Move::Stalemate
+        };
+        let events = next.play(&end.into()).ok()?;
+        over = events.iter().any(|e| matches!(e, GameEvent::GameOver(_)));
+    }
+    if over {
+        None
+    } else {
+        Some(next)
+    }
+}
+
+fn with_mates(m: &Move, mates: usize) -> Move {
+    match m {
+        Move::KingCastle(_) => Move::KingCastle(mates),
+        Move::QueenCastle(_) => Move::QueenCastle(mates),
+        Move::Normal(bm) => Move::Normal(BasicMove {
+            mates,
+            ..bm.clone()
+        }),
+        other => other.clone(),
+    }
+}
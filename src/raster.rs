@@ -0,0 +1,318 @@
+use crate::movegen::on_board;
+use crate::render::{move_squares, view_square};
+use crate::replay::{line_to, PositionError, Replay};
+use crate::svg::{piece_color, DARK, HIGHLIGHT, LIGHT, WALL};
+use crate::types::*;
+use fen4::{Board, Color, Piece, Position, TurnColor};
+
+use std::io;
+use std::time::Duration;
+use thiserror::Error;
+
+/// How pieces are drawn in raster images
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PieceSet {
+    /// Discs in their player's color with the piece letter on them, like the SVG diagrams
+    Discs,
+    /// Just the piece letter in their player's color
+    Letters,
+}
+
+/// Choices for drawing boards as raster images
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RasterOptions {
+    /// Width and height of a square in pixels
+    pub square_size: u32,
+    /// The player whose side of the board is at the bottom
+    pub bottom: TurnColor,
+    /// How pieces are drawn
+    pub piece_set: PieceSet,
+    /// Squares to highlight; frames of a game also highlight the move just played
+    pub highlight: Vec<Position>,
+    /// How long each frame of an animation is shown. GIFs store this in hundredths of a
+    /// second.
+    pub frame_delay: Duration,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            square_size: 32,
+            bottom: TurnColor::Red,
+            piece_set: PieceSet::Discs,
+            highlight: Vec::new(),
+            frame_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Possible errors while writing images
+#[derive(Error, Debug)]
+pub enum RasterError {
+    #[error("Unable to get the positions: {0}")]
+    Position(#[from] PositionError),
+    #[error("Unable to write the PNG: {0}")]
+    Png(#[from] png::EncodingError),
+    #[error("Unable to write the GIF: {0}")]
+    Gif(#[from] gif::EncodingError),
+    #[error("Images can be at most 65535 pixels wide for GIFs")]
+    TooLarge,
+}
+
+/// An 8-bit RGB image
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RgbImage {
+    pub width: u32,
+    pub height: u32,
+    /// Red, green and blue for each pixel, row by row from the top
+    pub pixels: Vec<u8>,
+}
+
+const BACKGROUND: &str = "#ffffff";
+const OUTLINE: &str = "#222222";
+
+/// Every color that is drawn, so GIFs can use an exact palette
+fn palette() -> Vec<[u8; 3]> {
+    let mut colors = vec![BACKGROUND, OUTLINE, LIGHT, DARK, WALL, HIGHLIGHT];
+    colors.extend(
+        [
+            Color::Turn(TurnColor::Red),
+            Color::Turn(TurnColor::Blue),
+            Color::Turn(TurnColor::Yellow),
+            Color::Turn(TurnColor::Green),
+            Color::Dead(None),
+        ]
+        .iter()
+        .map(|c| piece_color(*c)),
+    );
+    colors.into_iter().map(rgb).collect()
+}
+
+/// Reads a color like "#f0d9b5"
+fn rgb(hex: &str) -> [u8; 3] {
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap();
+    [channel(1), channel(3), channel(5)]
+}
+
+/// 5x7 letters, one row per byte with the leftmost pixel in bit 4
+fn glyph(letter: char) -> [u8; 7] {
+    match letter.to_ascii_uppercase() {
+        'A' => [14, 17, 17, 31, 17, 17, 17],
+        'B' => [30, 17, 17, 30, 17, 17, 30],
+        'C' => [14, 17, 16, 16, 16, 17, 14],
+        'D' => [30, 17, 17, 17, 17, 17, 30],
+        'E' => [31, 16, 16, 30, 16, 16, 31],
+        'F' => [31, 16, 16, 30, 16, 16, 16],
+        'G' => [14, 17, 16, 23, 17, 17, 15],
+        'H' => [17, 17, 17, 31, 17, 17, 17],
+        'I' => [14, 4, 4, 4, 4, 4, 14],
+        'J' => [7, 2, 2, 2, 2, 18, 12],
+        'K' => [17, 18, 20, 24, 20, 18, 17],
+        'L' => [16, 16, 16, 16, 16, 16, 31],
+        'M' => [17, 27, 21, 21, 17, 17, 17],
+        'N' => [17, 17, 25, 21, 19, 17, 17],
+        'O' => [14, 17, 17, 17, 17, 17, 14],
+        'P' => [30, 17, 17, 30, 16, 16, 16],
+        'Q' => [14, 17, 17, 17, 21, 18, 13],
+        'R' => [30, 17, 17, 30, 20, 18, 17],
+        'S' => [15, 16, 16, 14, 1, 1, 30],
+        'T' => [31, 4, 4, 4, 4, 4, 4],
+        'U' => [17, 17, 17, 17, 17, 17, 14],
+        'V' => [17, 17, 17, 17, 17, 10, 4],
+        'W' => [17, 17, 17, 21, 21, 21, 10],
+        'X' => [17, 17, 10, 4, 10, 17, 17],
+        'Y' => [17, 17, 10, 4, 4, 4, 4],
+        'Z' => [31, 1, 2, 4, 8, 16, 31],
+        _ => [14, 17, 1, 2, 4, 0, 4],
+    }
+}
+
+impl RgbImage {
+    fn new(width: u32, height: u32, color: [u8; 3]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: color
+                .iter()
+                .copied()
+                .cycle()
+                .take((width * height * 3) as usize)
+                .collect(),
+        }
+    }
+    fn set(&mut self, x: u32, y: u32, color: [u8; 3]) {
+        if x < self.width && y < self.height {
+            let i = ((y * self.width + x) * 3) as usize;
+            self.pixels[i..i + 3].copy_from_slice(&color);
+        }
+    }
+    fn fill(&mut self, left: u32, top: u32, width: u32, height: u32, color: [u8; 3]) {
+        for y in top..top + height {
+            for x in left..left + width {
+                self.set(x, y, color);
+            }
+        }
+    }
+    /// A filled circle with an outline, centered in the square at (`left`, `top`)
+    fn disc(&mut self, left: u32, top: u32, size: u32, color: [u8; 3]) {
+        let radius = (size * 2 / 5) as i64;
+        let center = (size / 2) as i64;
+        for dy in 0..size as i64 {
+            for dx in 0..size as i64 {
+                // Distances from the middle of the pixel, doubled to stay in integers
+                let (x, y) = (2 * (dx - center) + 1, 2 * (dy - center) + 1);
+                let distance = x * x + y * y;
+                if distance <= 4 * radius * radius {
+                    let edge = distance > 4 * (radius - 1) * (radius - 1);
+                    let c = if edge { rgb(OUTLINE) } else { color };
+                    self.set(left + dx as u32, top + dy as u32, c);
+                }
+            }
+        }
+    }
+    /// A letter `height` pixels tall (rounded down to a multiple of 7) centered in the
+    /// square at (`left`, `top`)
+    fn letter(
+        &mut self,
+        left: u32,
+        top: u32,
+        size: u32,
+        height: u32,
+        letter: char,
+        color: [u8; 3],
+    ) {
+        let scale = (height / 7).max(1);
+        let x0 = left + (size - (5 * scale).min(size)) / 2;
+        let y0 = top + (size - (7 * scale).min(size)) / 2;
+        for (row, bits) in glyph(letter).iter().enumerate() {
+            for col in 0..5 {
+                if bits & (16 >> col) != 0 {
+                    let (x, y) = (x0 + col * scale, y0 + row as u32 * scale);
+                    self.fill(x, y, scale, scale, color);
+                }
+            }
+        }
+    }
+
+    /// Encodes the image as a PNG
+    pub fn to_png(&self) -> Result<Vec<u8>, RasterError> {
+        let mut out = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels)?;
+        }
+        Ok(out)
+    }
+}
+
+/// Draws the board as an image 14 squares wide. Cut out corners are white.
+///
+/// Only drawing is done here; see [`RgbImage::to_png`] and [`PGN4::write_gif`] for files.
+pub fn render_image(board: &Board, options: &RasterOptions) -> RgbImage {
+    let size = options.square_size;
+    let mut image = RgbImage::new(14 * size, 14 * size, rgb(BACKGROUND));
+    for y in 0..14 {
+        for x in 0..14 {
+            let pos = view_square(options.bottom, x, y);
+            if !on_board(pos.row as isize, pos.col as isize) {
+                continue;
+            }
+            let (left, top) = (x as u32 * size, (13 - y as u32) * size);
+            let piece = &board.board[pos.row][pos.col];
+            let fill = if *piece == Piece::Wall {
+                WALL
+            } else if options.highlight.contains(&pos) {
+                HIGHLIGHT
+            } else if (pos.row + pos.col) % 2 == 0 {
+                DARK
+            } else {
+                LIGHT
+            };
+            image.fill(left, top, size, size, rgb(fill));
+            if let Piece::Normal(color, shape) = piece {
+                let color = rgb(piece_color(*color));
+                match options.piece_set {
+                    PieceSet::Discs => {
+                        image.disc(left, top, size, color);
+                        image.letter(left, top, size, size / 2, *shape, rgb(BACKGROUND));
+                    }
+                    PieceSet::Letters => {
+                        image.letter(left, top, size, size * 3 / 4, *shape, color);
+                    }
+                }
+            }
+        }
+    }
+    image
+}
+
+impl PGN4 {
+    /// Draws every position from the start of the game to the end of `path`, or to the
+    /// end of the mainline if there is no path. The move just played is highlighted.
+    pub fn render_frames(
+        &self,
+        path: Option<&[usize]>,
+        options: &RasterOptions,
+    ) -> Result<Vec<RgbImage>, PositionError> {
+        let played = match path {
+            Some(path) => line_to(&self.turns, path)?.0,
+            None => self.turns.iter().flat_map(|t| &t.turns).collect(),
+        };
+        let mut replay = Replay::new(self.variant()?);
+        let mut frames = vec![render_image(replay.board(), options)];
+        for qturn in played {
+            let board = replay.board();
+            let mut frame_options = options.clone();
+            frame_options
+                .highlight
+                .extend(move_squares(board, board.turn, &qturn.main));
+            replay.play(qturn)?;
+            frames.push(render_image(replay.board(), &frame_options));
+        }
+        Ok(frames)
+    }
+    /// [`render_frames`](PGN4::render_frames) as PNG files
+    pub fn png_frames(
+        &self,
+        path: Option<&[usize]>,
+        options: &RasterOptions,
+    ) -> Result<Vec<Vec<u8>>, RasterError> {
+        self.render_frames(path, options)?
+            .iter()
+            .map(RgbImage::to_png)
+            .collect()
+    }
+    /// Writes [`render_frames`](PGN4::render_frames) as a looping animated GIF
+    pub fn write_gif<W: io::Write>(
+        &self,
+        path: Option<&[usize]>,
+        options: &RasterOptions,
+        out: W,
+    ) -> Result<(), RasterError> {
+        let side = 14 * options.square_size;
+        if side > u16::MAX as u32 {
+            return Err(RasterError::TooLarge);
+        }
+        let frames = self.render_frames(path, options)?;
+        let palette = palette();
+        let flat: Vec<u8> = palette.iter().flatten().copied().collect();
+        let mut encoder = gif::Encoder::new(out, side as u16, side as u16, &flat)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        let delay = (options.frame_delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+        for image in frames {
+            let indices: Vec<u8> = image
+                .pixels
+                .chunks(3)
+                .map(|c| palette.iter().position(|p| p[..] == *c).unwrap_or(0) as u8)
+                .collect();
+            let mut frame =
+                gif::Frame::from_indexed_pixels(side as u16, side as u16, indices, None);
+            frame.delay = delay;
+            encoder.write_frame(&frame)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use crate::movegen::{castle_squares, on_board};
+use crate::replay::{line_to, PositionError, Replay};
+use crate::types::*;
+use fen4::{Board, Color, Piece, Position, TurnColor};
+
+/// Choices for drawing a board as text
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextOptions {
+    /// Color the pieces with ANSI escape codes. Without them each piece is written with
+    /// its fen4 color like "rK" or "dQ", and highlighted squares are marked with a '*'.
+    pub ansi: bool,
+    /// Write the files and ranks next to the board
+    pub coordinates: bool,
+    /// The player whose side of the board is at the bottom
+    pub bottom: TurnColor,
+    /// Squares to highlight, like the ones of the last move
+    pub highlight: Vec<Position>,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            ansi: false,
+            coordinates: true,
+            bottom: TurnColor::Red,
+            highlight: Vec::new(),
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const REVERSE: &str = "\x1b[7m";
+
+/// The ANSI foreground color for pieces of `color`
+fn ansi_color(color: Color) -> &'static str {
+    match color {
+        Color::Turn(TurnColor::Red) => "\x1b[31m",
+        Color::Turn(TurnColor::Blue) => "\x1b[34m",
+        Color::Turn(TurnColor::Yellow) => "\x1b[33m",
+        Color::Turn(TurnColor::Green) => "\x1b[32m",
+        Color::Dead(_) => "\x1b[90m",
+    }
+}
+
+/// The square shown at (`x`, `y`) counting from the bottom left when `bottom` is at the
+/// bottom. Each color clockwise from Red turns the board a quarter turn.
+pub(crate) fn view_square(bottom: TurnColor, x: usize, y: usize) -> Position {
+    let (mut x, mut y) = (x, y);
+    for _ in 0..usize::from(bottom) {
+        let turned = (y, 13 - x);
+        x = turned.0;
+        y = turned.1;
+    }
+    Position { row: y, col: x }
+}
+
+/// The label for a line of squares from `a` to `b`: the rank if they share it, else the file
+pub(crate) fn label(a: Position, b: Position) -> String {
+    let name = a.to_string();
+    if a.row == b.row {
+        name[1..].to_owned()
+    } else {
+        name[..1].to_owned()
+    }
+}
+
+/// Draws the board as lines of text. Cut out corners are left blank and walls are drawn with
+/// "#"s.
+pub fn render_text(board: &Board, options: &TextOptions) -> String {
+    let width = if options.ansi { 3 } else { 4 };
+    let mut out = String::new();
+    for y in (0..14).rev() {
+        if options.coordinates {
+            let rank = label(
+                view_square(options.bottom, 0, y),
+                view_square(options.bottom, 13, y),
+            );
+            out.push_str(&format!("{:>2} ", rank));
+        }
+        for x in 0..14 {
+            let pos = view_square(options.bottom, x, y);
+            out.push_str(&cell(board, pos, options));
+        }
+        out.truncate(out.trim_end_matches(' ').len());
+        out.push('\n');
+    }
+    if options.coordinates {
+        out.push_str("   ");
+        for x in 0..14 {
+            let file = label(
+                view_square(options.bottom, x, 0),
+                view_square(options.bottom, x, 13),
+            );
+            out.push_str(&format!(" {:<w$}", file, w = width - 1));
+        }
+        out.truncate(out.trim_end_matches(' ').len());
+        out.push('\n');
+    }
+    out
+}
+
+fn cell(board: &Board, pos: Position, options: &TextOptions) -> String {
+    if !on_board(pos.row as isize, pos.col as isize) {
+        return " ".repeat(if options.ansi { 3 } else { 4 });
+    }
+    let highlighted = options.highlight.contains(&pos);
+    let piece = &board.board[pos.row][pos.col];
+    if options.ansi {
+        let (color, text) = match piece {
+            Piece::Empty => ("", " . ".to_owned()),
+            Piece::Wall => ("", "###".to_owned()),
+            Piece::Normal(color, shape) => (ansi_color(*color), format!(" {} ", shape)),
+        };
+        let reverse = if highlighted { REVERSE } else { "" };
+        if color.is_empty() && reverse.is_empty() {
+            text
+        } else {
+            format!("{}{}{}{}", color, reverse, text, RESET)
+        }
+    } else {
+        let text = match piece {
+            Piece::Empty => ".".to_owned(),
+            Piece::Wall => "##".to_owned(),
+            Piece::Normal(..) => piece.to_string(),
+        };
+        format!("{}{:<3}", if highlighted { '*' } else { ' ' }, text)
+    }
+}
+
+/// The squares to highlight for `m` played by `color` on `board`
+pub(crate) fn move_squares(board: &Board, color: TurnColor, m: &Move) -> Vec<Position> {
+    match m {
+        Move::Normal(bm) => vec![bm.from, bm.to],
+        Move::KingCastle(_) | Move::QueenCastle(_) => {
+            let kingside = matches!(m, Move::KingCastle(_));
+            castle_squares(board, color, kingside)
+                .map(|(king_from, king_to, _, _)| vec![king_from, king_to])
+                .unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
+
+impl PGN4 {
+    /// The position after following `path` (like [`fen4_at`](PGN4::fen4_at)) and the
+    /// squares of the move that was played last
+    pub fn position_at(&self, path: &[usize]) -> Result<(Board, Vec<Position>), PositionError> {
+        let mut replay = Replay::new(self.variant()?);
+        let mut last = Vec::new();
+        for qturn in line_to(&self.turns, path)?.0 {
+            let board = replay.board();
+            last = move_squares(board, board.turn, &qturn.main);
+            replay.play(qturn)?;
+        }
+        Ok((replay.into_board(), last))
+    }
+    /// Draws the position after following `path`, highlighting the last move
+    pub fn render_text_at(
+        &self,
+        path: &[usize],
+        options: &TextOptions,
+    ) -> Result<String, PositionError> {
+        let (board, last) = self.position_at(path)?;
+        let mut options = options.clone();
+        options.highlight.extend(last);
+        Ok(render_text(&board, &options))
+    }
+}
@@ -0,0 +1,681 @@
+use crate::hash::*;
+use crate::movegen::*;
+use crate::path::check;
+use crate::rules::*;
+use crate::traits::VariantError;
+use crate::types::*;
+use crate::visitor::VisitingError;
+use fen4::{Board, Color, Piece, Position, TurnColor};
+
+use thiserror::Error;
+
+/// Possible errors while playing moves onto a board
+#[derive(Error, PartialEq, Debug, Clone)]
+pub enum ReplayError {
+    #[error("There is no piece of the player to move on {0}")]
+    NoPiece(Position),
+    #[error("The piece on {0} is not a '{1}'")]
+    WrongPiece(Position, char),
+    #[error("Move \"{0}\" does not capture what it claims to")]
+    WrongCapture(String),
+    #[error("Move \"{0}\" is not legal in this position")]
+    IllegalMove(String),
+    #[error("\"{0}\" is not a possible combination of moves for the player to move")]
+    BadModifier(String),
+    #[error("Move \"{0}\" claims to end more games than it does")]
+    MissingMate(String),
+    #[error("There are no players left to move")]
+    GameOver,
+}
+
+/// Possible errors while finding the position at a path
+#[derive(Error, PartialEq, Debug, Clone)]
+pub enum PositionError {
+    #[error("Unable to follow the path because: {0}")]
+    Path(#[from] VisitingError),
+    #[error("Unable to determine the variant because: {0}")]
+    Variant(#[from] VariantError),
+    #[error("Unable to replay the moves because: {0}")]
+    Replay(#[from] ReplayError),
+}
+
+/// A board together with the rules moves are played by
+///
+/// Beyond moving pieces, this tracks eliminations, points, castling rights and en passant
+/// in the board's metadata. With "DeadKingWalking" a player that resigns or times out keeps
+/// their king, which continues to take turns (notated like "Ka4-b5R"). With "Takeover" the
+/// army of a checkmated player joins the player who delivered the checkmate.
+///
+/// The win conditions of "KotH", "N-check", "CaptureTheKing" and "Antichess" are followed
+/// and reported as [`GameEvent`]s.
+///
+/// The [`zobrist`] hash of the board is kept up to date as moves are played.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    board: Board,
+    variant: Variant,
+    hash: u64,
+    /// Events caused by the quarter turn being played
+    events: Vec<GameEvent>,
+}
+
+impl Replay {
+    /// Starts from the initial board of the variant
+    pub fn new(variant: Variant) -> Self {
+        Self::from_board(variant.initial_board.clone(), variant)
+    }
+    /// Starts from an arbitrary board
+    pub fn from_board(board: Board, variant: Variant) -> Self {
+        Self {
+            hash: zobrist(&board),
+            board,
+            variant,
+            events: Vec::new(),
+        }
+    }
+    /// The current board
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+    /// The rules being played by
+    pub fn variant(&self) -> &Variant {
+        &self.variant
+    }
+    /// The [`zobrist`] hash of the current board
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+    /// Consumes the replay, keeping only the board
+    pub fn into_board(self) -> Board {
+        self.board
+    }
+    /// Has the player left the game, but still has a king walking around
+    pub fn is_zombie(&self, color: TurnColor) -> bool {
+        is_zombie(&self.board, &self.variant, color)
+    }
+    /// Does the player still take turns
+    pub fn in_play(&self, color: TurnColor) -> bool {
+        in_play(&self.board, &self.variant, color)
+    }
+    /// All legal moves for the player to move
+    pub fn legal_moves(&self) -> Vec<Move> {
+        legal_moves(&self.board, &self.variant)
+    }
+
+    /// Plays a quarter turn for the player to move and passes the turn on.
+    /// Returns any players leaving the game or the game ending because of it.
+    ///
+    /// On error, the replay is left unchanged.
+    pub fn play(&mut self, q: &QuarterTurn) -> Result<Vec<GameEvent>, ReplayError> {
+        let mut next = self.clone();
+        next.play_unchecked(q)?;
+        let events = std::mem::take(&mut next.events);
+        *self = next;
+        Ok(events)
+    }
+
+    fn play_unchecked(&mut self, q: &QuarterTurn) -> Result<(), ReplayError> {
+        use Move::*;
+        let color = self.board.turn;
+        if !self.in_play(color) {
+            return Err(ReplayError::GameOver);
+        }
+        let state = state_key(&self.board);
+        let bad_modifier = || ReplayError::BadModifier(q.to_string());
+        match (&q.main, &q.modifier) {
+            (Resign, None) | (Timeout, None) => self.leave(color, &q.main),
+            (Resign, Some(end)) | (Timeout, Some(end)) if matches!(end, Checkmate | Stalemate) => {
+                self.leave(color, &q.main);
+                self.end(color, end);
+            }
+            (Checkmate, None) | (Stalemate, None) => self.end(color, &q.main),
+            (m, None) => self.play_move(color, m)?,
+            (m @ Normal(bm), Some(Resign)) | (m @ Normal(bm), Some(Timeout)) => {
+                // Only the king of a player that has left the game moves on its own
+                if !self.is_zombie(color) || !royal_squares(&self.board, color).contains(&bm.from) {
+                    return Err(bad_modifier());
+                }
+                self.play_move(color, m)?;
+            }
+            (m, Some(Stalemate)) if !matches!(m, Resign | Timeout | Checkmate | Stalemate) => {
+                self.play_move(color, m)?;
+                self.end_stuck(color, 1, false, q)?;
+            }
+            _ => return Err(bad_modifier()),
+        }
+        if q.extra_stalemate {
+            self.end_stuck(color, 1, false, q)?;
+        }
+        let over = self
+            .events
+            .iter()
+            .any(|e| matches!(e, GameEvent::GameOver(_)));
+        if !over && !self.events.is_empty() {
+            if let Some(won) = winners(&self.board, &self.variant) {
+                self.events.push(GameEvent::GameOver(won));
+            }
+        }
+        self.pass_turn();
+        self.hash ^= state ^ state_key(&self.board);
+        Ok(())
+    }
+
+    /// The player to move is checkmated or stalemated on their own turn
+    fn end(&mut self, color: TurnColor, how: &Move) {
+        if let Move::Checkmate = how {
+            self.eliminate(color, None, GameEvent::Checkmated(color, None));
+        } else {
+            self.stalemated(color);
+        }
+    }
+
+    fn stalemated(&mut self, color: TurnColor) {
+        self.eliminate(color, None, GameEvent::Stalemated(color));
+        if self.variant.antichess {
+            self.events
+                .push(GameEvent::GameOver(self.variant.team(color)));
+        }
+    }
+
+    /// Resignation or timeout. The player's pieces die, except for the king in "DeadKingWalking"
+    fn leave(&mut self, color: TurnColor, how: &Move) {
+        let index = usize::from(color);
+        if let Move::Resign = how {
+            self.board.extra_options.resigned[index] = true;
+            self.events.push(GameEvent::Resigned(color));
+        } else {
+            self.board.extra_options.flagged[index] = true;
+            self.events.push(GameEvent::TimedOut(color));
+        }
+        self.board.dead[index] = true;
+        self.board.castling_king[index] = false;
+        self.board.castling_queen[index] = false;
+        let royals = if self.variant.ffa_dead_king_walking {
+            royal_squares(&self.board, color)
+        } else {
+            Vec::new()
+        };
+        for row in 0..14 {
+            for col in 0..14 {
+                if royals.contains(&Position { row, col }) {
+                    continue;
+                }
+                if let Piece::Normal(Color::Turn(c), shape) = self.board.board[row][col] {
+                    if c == color {
+                        let dead = Piece::Normal(Color::Dead(Some(c)), shape);
+                        self.set_square(Position { row, col }, dead);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes a player from the game entirely. If `taker` is given and "Takeover" is
+    /// enabled, their pieces (except royal ones) are handed over instead of dying.
+    fn eliminate(&mut self, color: TurnColor, taker: Option<TurnColor>, event: GameEvent) {
+        self.events.push(event);
+        let taker = taker.filter(|_| self.variant.ffa_takeover && !self.variant.teams());
+        let royals = royal_squares(&self.board, color);
+        let index = usize::from(color);
+        self.board.dead[index] = true;
+        self.board.castling_king[index] = false;
+        self.board.castling_queen[index] = false;
+        for row in 0..14 {
+            for col in 0..14 {
+                if let Piece::Normal(Color::Turn(c), shape) = self.board.board[row][col] {
+                    if c == color {
+                        let new_color = match taker {
+                            Some(t) if !royals.contains(&Position { row, col }) => Color::Turn(t),
+                            _ => Color::Dead(Some(c)),
+                        };
+                        self.set_square(Position { row, col }, Piece::Normal(new_color, shape));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Eliminates the next `count` players (after `mover`) that are checkmated (or stalemated)
+    fn end_stuck(
+        &mut self,
+        mover: TurnColor,
+        count: usize,
+        mate: bool,
+        q: &QuarterTurn,
+    ) -> Result<(), ReplayError> {
+        let mut found = Vec::new();
+        let mut color = mover.next();
+        while color != mover && found.len() < count {
+            if self.in_play(color) && stuck(&self.board, &self.variant, color, mate) {
+                found.push(color);
+            }
+            color = color.next();
+        }
+        if found.len() < count {
+            return Err(ReplayError::MissingMate(q.to_string()));
+        }
+        for color in found {
+            if !mate {
+                self.stalemated(color);
+                continue;
+            }
+            if !self.variant.teams() {
+                self.board.points[usize::from(mover)] += self.variant.ffa_points_for_mate;
+            }
+            self.eliminate(
+                color,
+                Some(mover),
+                GameEvent::Checkmated(color, Some(mover)),
+            );
+        }
+        Ok(())
+    }
+
+    fn play_move(&mut self, color: TurnColor, m: &Move) -> Result<(), ReplayError> {
+        let index = usize::from(color);
+        let legal = legal_moves_for(&self.board, &self.variant, color);
+        let mates = match m {
+            Move::KingCastle(mates) | Move::QueenCastle(mates) => {
+                let kingside = matches!(m, Move::KingCastle(_));
+                let wanted = if kingside {
+                    Move::KingCastle(0)
+                } else {
+                    Move::QueenCastle(0)
+                };
+                if !legal.contains(&wanted) {
+                    return Err(ReplayError::IllegalMove(m.to_string()));
+                }
+                move_royal(&mut self.board, color, m);
+                self.move_pieces(color, m);
+                self.board.castling_king[index] = false;
+                self.board.castling_queen[index] = false;
+                self.board.extra_options.enpassant[index] = None;
+                self.board.draw_ply += 1;
+                *mates
+            }
+            Move::Normal(bm) => {
+                self.play_basic(color, bm, &legal)?;
+                bm.mates
+            }
+            _ => return Err(ReplayError::IllegalMove(m.to_string())),
+        };
+        if self.board.extra_options.lives.is_some() {
+            self.take_lives(color);
+        }
+        if mates > 0 {
+            self.end_stuck(color, mates, true, &m.clone().into())?;
+        }
+        Ok(())
+    }
+
+    /// "N-check": every player checked by the mover loses a life
+    fn take_lives(&mut self, mover: TurnColor) {
+        for color in &COLORS {
+            if !self.in_play(*color) || !checked_by(&self.board, &self.variant, *color, mover) {
+                continue;
+            }
+            let lives = match &mut self.board.extra_options.lives {
+                Some(lives) => &mut lives[usize::from(*color)],
+                None => return,
+            };
+            *lives = lives.saturating_sub(1);
+            if *lives == 0 {
+                let event = GameEvent::ChecksExhausted(*color, mover);
+                self.eliminate(*color, Some(mover), event);
+            }
+        }
+    }
+
+    fn play_basic(
+        &mut self,
+        color: TurnColor,
+        bm: &BasicMove,
+        legal: &[Move],
+    ) -> Result<(), ReplayError> {
+        let index = usize::from(color);
+        match &self.board.board[bm.from.row][bm.from.col] {
+            Piece::Normal(Color::Turn(c), shape) if *c == color => {
+                if *shape != bm.piece {
+                    return Err(ReplayError::WrongPiece(bm.from, bm.piece));
+                }
+            }
+            _ => return Err(ReplayError::NoPiece(bm.from)),
+        }
+        let is_legal = legal.iter().any(|l| match l {
+            Move::Normal(l) => {
+                l.from == bm.from
+                    && l.to == bm.to
+                    && l.promotion.is_some() == bm.promotion.is_some()
+            }
+            _ => false,
+        });
+        if !is_legal {
+            return Err(ReplayError::IllegalMove(bm.to_string()));
+        }
+
+        let target = self.board.board[bm.to.row][bm.to.col].clone();
+        let passed = if bm.piece == 'P' && target.is_empty() {
+            en_passant_target(&self.board, &self.variant, bm.to, color)
+        } else {
+            None
+        };
+        let captured = match (&target, passed) {
+            (Piece::Normal(_, shape), _) => Some(*shape),
+            (_, Some(_)) => Some('P'),
+            _ => None,
+        };
+        if captured != bm.captured {
+            return Err(ReplayError::WrongCapture(bm.to_string()));
+        }
+        let captured_piece = match passed {
+            Some(p) => self.board.board[p.row][p.col].clone(),
+            None => target,
+        };
+        let captured_royal = match &captured_piece {
+            Piece::Normal(Color::Turn(c), _) => {
+                let royals = royal_squares(&self.board, *c);
+                if royals.contains(&bm.to) && royals.len() == 1 {
+                    Some(*c)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        // Castling rights are lost by moving the king or either rook
+        let moving_royal = royal_squares(&self.board, color).contains(&bm.from);
+        if moving_royal || bm.piece == 'K' {
+            self.board.castling_king[index] = false;
+            self.board.castling_queen[index] = false;
+        }
+        self.lose_rook_rights(color, bm.from);
+        if let Piece::Normal(Color::Turn(c), 'R') = captured_piece {
+            self.lose_rook_rights(c, bm.to);
+        }
+        let m = Move::Normal(bm.clone());
+        move_royal(&mut self.board, color, &m);
+        self.move_pieces(color, &m);
+
+        let enpassant = &mut self.board.extra_options.enpassant[index];
+        *enpassant = None;
+        let (fr, fc) = forward(color);
+        let row = bm.from.row as isize + 2 * fr;
+        let col = bm.from.col as isize + 2 * fc;
+        if self.variant.en_passant
+            && bm.piece == 'P'
+            && (row, col) == (bm.to.row as isize, bm.to.col as isize)
+        {
+            let middle = Position {
+                row: (bm.from.row as isize + fr) as usize,
+                col: (bm.from.col as isize + fc) as usize,
+            };
+            *enpassant = Some((middle, bm.to));
+        }
+
+        if bm.piece == 'P' || captured.is_some() {
+            self.board.draw_ply = 0;
+        } else {
+            self.board.draw_ply += 1;
+        }
+        if !self.variant.teams() {
+            if let Piece::Normal(Color::Turn(_), shape) = captured_piece {
+                self.board.points[index] += piece_points(shape);
+            }
+        }
+        if let Some(c) = captured_royal {
+            self.eliminate(c, Some(color), GameEvent::KingCaptured(c, color));
+        }
+        if self.variant.king_of_the_hill && moving_royal && on_hill(bm.to) {
+            self.events.push(GameEvent::ReachedHill(color));
+            self.events
+                .push(GameEvent::GameOver(self.variant.team(color)));
+        }
+        Ok(())
+    }
+
+    fn set_square(&mut self, pos: Position, piece: Piece) {
+        let square = &mut self.board.board[pos.row][pos.col];
+        self.hash ^= square_key(pos, square) ^ square_key(pos, &piece);
+        *square = piece;
+    }
+
+    /// [`move_pieces`], keeping the hash up to date
+    fn move_pieces(&mut self, color: TurnColor, m: &Move) {
+        let changed = match m {
+            Move::KingCastle(_) | Move::QueenCastle(_) => {
+                let kingside = matches!(m, Move::KingCastle(_));
+                match castle_squares(&self.board, color, kingside) {
+                    Some((king_from, king_to, rook_from, rook_to)) => {
+                        vec![king_from, king_to, rook_from, rook_to]
+                    }
+                    None => Vec::new(),
+                }
+            }
+            Move::Normal(bm) => {
+                let mut changed = vec![bm.from, bm.to];
+                changed.extend(en_passant_target(&self.board, &self.variant, bm.to, color));
+                changed
+            }
+            _ => Vec::new(),
+        };
+        // Castling squares can overlap, so each square is only counted once
+        let mut squares: Vec<Position> = Vec::new();
+        for pos in changed {
+            if !squares.contains(&pos) {
+                squares.push(pos);
+            }
+        }
+        let before: Vec<Piece> = squares
+            .iter()
+            .map(|p| self.board.board[p.row][p.col].clone())
+            .collect();
+        move_pieces(&mut self.board, &self.variant, color, m);
+        for (pos, old) in squares.iter().zip(before) {
+            let new = &self.board.board[pos.row][pos.col];
+            self.hash ^= square_key(*pos, &old) ^ square_key(*pos, new);
+        }
+    }
+
+    /// A rook of `color` left (or was captured on) `square`; lose castling rights on that side
+    fn lose_rook_rights(&mut self, color: TurnColor, square: Position) {
+        let index = usize::from(color);
+        if self.board.board[square.row][square.col] != Piece::Normal(Color::Turn(color), 'R') {
+            return;
+        }
+        let rook_i = match back_rank_index(color, square) {
+            Some(i) => i,
+            None => return,
+        };
+        let king_i = (0..8).find(|i| {
+            let p = back_rank(color, *i);
+            self.board.board[p.row][p.col] == Piece::Normal(Color::Turn(color), 'K')
+        });
+        match king_i {
+            Some(king_i) if rook_i > king_i => self.board.castling_king[index] = false,
+            Some(_) => self.board.castling_queen[index] = false,
+            None => {}
+        }
+    }
+
+    fn pass_turn(&mut self) {
+        let mut color = self.board.turn.next();
+        while color != self.board.turn {
+            if self.in_play(color) {
+                self.board.turn = color;
+                return;
+            }
+            color = color.next();
+        }
+    }
+}
+
+/// Points awarded in FFA for capturing a living piece
+fn piece_points(shape: char) -> u16 {
+    match shape {
+        'P' | 'D' => 1,
+        'N' => 3,
+        'B' | 'R' => 5,
+        'Q' => 9,
+        _ => 0,
+    }
+}
+
+impl PGN4 {
+    /// Plays the mainline of the game, returning the board before every quarter turn and after the last one
+    pub fn replay_mainline(&self, variant: &Variant) -> Result<Vec<Board>, ReplayError> {
+        let mut replay = Replay::new(variant.clone());
+        let mut out = vec![replay.board().clone()];
+        for turn in &self.turns {
+            for qturn in &turn.turns {
+                replay.play(qturn)?;
+                out.push(replay.board().clone());
+            }
+        }
+        Ok(out)
+    }
+    /// The position after following `path` from the start of the game.
+    ///
+    /// `path` uses the same format as [`append_move`](PGN4::append_move); `[0]` is the
+    /// starting position. The variant (and starting position) are taken from the tags.
+    pub fn fen4_at(&self, path: &[usize]) -> Result<Board, PositionError> {
+        let mut replay = Replay::new(self.variant()?);
+        for qturn in line_to(&self.turns, path)?.0 {
+            replay.play(qturn)?;
+        }
+        Ok(replay.into_board())
+    }
+    /// Makes a new game that starts from the position at `path` and continues with the
+    /// rest of the line `path` is in (including its variations).
+    ///
+    /// The "StartFen4" tag is set to the position and the turns are renumbered from 1.
+    pub fn starting_from(&self, path: &[usize]) -> Result<PGN4, PositionError> {
+        let mut replay = Replay::new(self.variant()?);
+        let (played, rest) = line_to(&self.turns, path)?;
+        for qturn in played {
+            replay.play(qturn)?;
+        }
+        let fen = replay.board().to_string().replace('\n', "");
+        let mut bracketed = Vec::new();
+        let mut has_fen = false;
+        for (key, value) in &self.bracketed {
+            match key.as_str() {
+                "StartFen4" => {
+                    has_fen = true;
+                    bracketed.push((key.clone(), fen.clone()));
+                }
+                // A Chess960 number would contradict the new starting position
+                "RuleVariants" => {
+                    let rules: Vec<&str> = value
+                        .split(' ')
+                        .filter(|r| !r.starts_with("Chess960="))
+                        .collect();
+                    bracketed.push((key.clone(), rules.join(" ")));
+                }
+                _ => bracketed.push((key.clone(), value.clone())),
+            }
+        }
+        if !has_fen {
+            bracketed.push(("StartFen4".to_owned(), fen));
+        }
+        let rest: Vec<QuarterTurn> = rest.into_iter().cloned().collect();
+        let starts_turn = replay.starts_turn();
+        let turns = renumber(replay, &rest, 1, starts_turn, false)?;
+        Ok(PGN4 { bracketed, turns })
+    }
+}
+
+impl Replay {
+    /// Is the player to move the first player in a turn
+    fn starts_turn(&self) -> bool {
+        COLORS.iter().find(|c| self.in_play(**c)) == Some(&self.board.turn)
+    }
+}
+
+/// Groups a line of quarter turns played from `replay` into numbered turns, doing the same
+/// for all of their alternatives.
+///
+/// `number` is the number of the turn the first quarter turn is in.
+fn renumber(
+    mut replay: Replay,
+    line: &[QuarterTurn],
+    mut number: usize,
+    starts_turn: bool,
+    alternative: bool,
+) -> Result<Vec<Turn>, ReplayError> {
+    let mut turns: Vec<Turn> = Vec::new();
+    let mut previous = None;
+    for qturn in line {
+        let color = replay.board().turn;
+        let new_turn = match previous {
+            None => true,
+            Some(p) => usize::from(color) <= usize::from(p),
+        };
+        if turns.is_empty() {
+            // Lines that start in the middle of a turn only show the number in the mainline
+            turns.push(Turn {
+                number: if starts_turn || !alternative {
+                    number
+                } else {
+                    0
+                },
+                double_dot: alternative || !starts_turn,
+                turns: Vec::new(),
+            });
+        } else if new_turn {
+            number += 1;
+            turns.push(Turn {
+                number,
+                double_dot: false,
+                turns: Vec::new(),
+            });
+        }
+        let mut alternatives = Vec::new();
+        let first = turns.len() == 1 && turns[0].turns.is_empty();
+        let starts = if first { starts_turn } else { new_turn };
+        for alt in &qturn.alternatives {
+            let alt: Vec<QuarterTurn> = alt.iter().flat_map(|t| t.turns.clone()).collect();
+            alternatives.push(renumber(replay.clone(), &alt, number, starts, true)?);
+        }
+        replay.play(qturn)?;
+        turns.last_mut().unwrap().turns.push(QuarterTurn {
+            alternatives,
+            ..qturn.clone()
+        });
+        previous = Some(color);
+    }
+    Ok(turns)
+}
+
+/// The quarter turns played from the start of `turns` to reach the end of `path`, and the
+/// rest of the line `path` ends in.
+pub(crate) fn line_to<'a>(
+    turns: &'a [Turn],
+    path: &[usize],
+) -> Result<(Vec<&'a QuarterTurn>, Vec<&'a QuarterTurn>), VisitingError> {
+    check(path)?;
+    let mut line: Vec<&QuarterTurn> = turns.iter().flat_map(|t| &t.turns).collect();
+    if path == [0] {
+        return Ok((Vec::new(), line));
+    }
+    let mut out = Vec::new();
+    let mut rest = path;
+    loop {
+        let ply = rest[0];
+        if ply > line.len() {
+            return Err(VisitingError::UnexpectedEndOfGame);
+        }
+        if rest.len() == 1 {
+            out.extend(&line[..ply]);
+            return Ok((out, line.split_off(ply)));
+        }
+        // The alternative replaces the quarter turn it is attached to
+        out.extend(&line[..ply - 1]);
+        let alternative = line[ply - 1]
+            .alternatives
+            .get(rest[1] - 1)
+            .ok_or(VisitingError::InvalidAlternative)?;
+        line = alternative.iter().flat_map(|t| &t.turns).collect();
+        rest = &rest[2..];
+    }
+}
@@ -0,0 +1,80 @@
+use crate::movegen::*;
+use crate::types::*;
+use fen4::{Board, Position, TurnColor};
+
+/// Something that ended the game for a player or ended the game as a whole
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GameEvent {
+    /// The player resigned
+    Resigned(TurnColor),
+    /// The player ran out of time
+    TimedOut(TurnColor),
+    /// The player was checkmated, by the player whose move caused it if that is known
+    Checkmated(TurnColor, Option<TurnColor>),
+    /// The player had no legal moves. In "Antichess" this wins the game.
+    Stalemated(TurnColor),
+    /// The player's king was captured by the second player
+    KingCaptured(TurnColor, TurnColor),
+    /// The player used up their lives in "N-check"; the second player gave the last check
+    ChecksExhausted(TurnColor, TurnColor),
+    /// The player's king reached the centre in "KotH", winning the game
+    ReachedHill(TurnColor),
+    /// The game is over and these players won
+    GameOver(Vec<TurnColor>),
+}
+
+/// Is the square one of the four centre squares a king has to reach in "KotH"
+pub fn on_hill(pos: Position) -> bool {
+    (6..=7).contains(&pos.row) && (6..=7).contains(&pos.col)
+}
+
+/// Has the player left the game, but still has a king walking around ("DeadKingWalking")
+pub fn is_zombie(board: &Board, variant: &Variant, color: TurnColor) -> bool {
+    let index = usize::from(color);
+    let extra = &board.extra_options;
+    variant.ffa_dead_king_walking
+        && board.dead[index]
+        && (extra.resigned[index] || extra.flagged[index])
+        && !royal_squares(board, color).is_empty()
+}
+
+/// Does the player still take turns
+pub fn in_play(board: &Board, variant: &Variant, color: TurnColor) -> bool {
+    !board.dead[usize::from(color)] || is_zombie(board, variant, color)
+}
+
+/// Is the player checkmated (or stalemated if `mate` is false) if it were their turn
+pub fn stuck(board: &Board, variant: &Variant, color: TurnColor, mate: bool) -> bool {
+    let checked = royal_rules(variant) && in_check(board, variant, color);
+    checked == mate && legal_moves_for(board, variant, color).is_empty()
+}
+
+/// If the game is over because players left, the players who won.
+///
+/// In teams, the game is over as soon as anyone leaves and the other team wins.
+/// Otherwise the game is over when only one player is left and the player with the most
+/// points wins; ties share the win.
+pub fn winners(board: &Board, variant: &Variant) -> Option<Vec<TurnColor>> {
+    if variant.teams() {
+        let lost = *COLORS.iter().find(|c| board.dead[usize::from(**c)])?;
+        return Some(
+            COLORS
+                .iter()
+                .copied()
+                .filter(|c| !variant.team(lost).contains(c))
+                .collect(),
+        );
+    }
+    let left = board.dead.iter().filter(|d| !**d).count();
+    if left > 1 {
+        return None;
+    }
+    let best = *board.points.iter().max().unwrap();
+    Some(
+        COLORS
+            .iter()
+            .copied()
+            .filter(|c| board.points[usize::from(*c)] == best)
+            .collect(),
+    )
+}
@@ -0,0 +1,58 @@
+//! Serde support for the fen4 types used in this crate, which are written as their fen4 strings
+
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Error as _, Serializer};
+
+/// A square like "d4"
+pub(crate) mod position {
+    use super::*;
+    use fen4::Position;
+
+    pub(crate) fn serialize<S: Serializer>(pos: &Position, s: S) -> Result<S::Ok, S::Error> {
+        if pos.row >= 14 || pos.col >= 14 {
+            return Err(S::Error::custom(format!("{:?} is off the board", pos)));
+        }
+        s.collect_str(pos)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Position, D::Error> {
+        let text = String::deserialize(d)?;
+        text.parse()
+            .map_err(|e| D::Error::custom(format!("bad square \"{}\": {:?}", text, e)))
+    }
+}
+
+/// A full fen4 on a single line
+pub(crate) mod board {
+    use super::*;
+    use fen4::Board;
+
+    pub(crate) fn serialize<S: Serializer>(board: &Board, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&board.to_string().replace('\n', ""))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Board, D::Error> {
+        let text = String::deserialize(d)?;
+        text.parse()
+            .map_err(|e| D::Error::custom(format!("bad fen4 \"{}\": {}", text, e)))
+    }
+}
+
+/// The color prefix of a piece, like "r" or "dg"
+pub(crate) mod color {
+    use super::*;
+    use fen4::{Color, Piece};
+
+    pub(crate) fn serialize<S: Serializer>(color: &Color, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(color)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(d)?;
+        // fen4 only parses colors as part of a piece
+        match format!("{}K", text).parse() {
+            Ok(Piece::Normal(color, 'K')) if color.to_string() == text => Ok(color),
+            _ => Err(D::Error::custom(format!("bad color \"{}\"", text))),
+        }
+    }
+}
@@ -0,0 +1,252 @@
+use crate::letters::PieceLetters;
+use crate::movegen::*;
+use crate::replay::{PositionError, Replay, ReplayError};
+use crate::types::*;
+use crate::writer::{WriteOptions, Writer};
+use fen4::{Board, Position};
+
+use std::fmt;
+use thiserror::Error;
+
+/// Possible errors while reading a move in short notation
+#[derive(Error, PartialEq, Debug, Clone)]
+pub enum ShortError {
+    #[error("\"{0}\" is not a move")]
+    Malformed(String),
+    #[error("No legal move matches \"{0}\"")]
+    NoMatch(String),
+    #[error("More than one legal move matches \"{0}\"")]
+    Ambiguous(String),
+}
+
+/// Writes a move in short notation (like "Ne5", "Nxe5" or "bxc6") for the player to move.
+///
+/// The starting square is only given as much as needed to tell apart moves of the same kind
+/// of piece to the same square. Pawn captures always give the file or rank they came from.
+/// Moves that are not legal on `board` are written in long notation.
+pub fn to_short(board: &Board, variant: &Variant, m: &Move) -> String {
+    let bm = match m {
+        Move::Normal(bm) => bm,
+        _ => return m.to_string(),
+    };
+    let legal = legal_moves(board, variant);
+    let same_target: Vec<&BasicMove> = legal
+        .iter()
+        .filter_map(|l| match l {
+            Move::Normal(l) if l.piece == bm.piece && l.to == bm.to => Some(l),
+            _ => None,
+        })
+        .collect();
+    if !same_target.iter().any(|l| l.from == bm.from) {
+        return m.to_string();
+    }
+    let others: Vec<Position> = same_target
+        .iter()
+        .map(|l| l.from)
+        .filter(|from| *from != bm.from)
+        .collect();
+    let (file, rank) = split_square(bm.from);
+
+    let mut out = String::new();
+    if bm.piece != 'P' {
+        out.push(bm.piece);
+    }
+    let pawn_capture = bm.piece == 'P' && bm.captured.is_some();
+    if !others.is_empty() || pawn_capture {
+        if others.iter().all(|p| p.col != bm.from.col) {
+            out.push(file);
+        } else if others.iter().all(|p| p.row != bm.from.row) {
+            out.push_str(&rank);
+        } else {
+            out.push_str(&bm.from.to_string());
+        }
+    }
+    if bm.captured.is_some() {
+        out.push('x');
+    }
+    out.push_str(&bm.to.to_string());
+    if let Some(p) = bm.promotion {
+        out.push('=');
+        out.push(p);
+    }
+    out.push_str(&"+".repeat(bm.checks));
+    out.push_str(&"#".repeat(bm.mates));
+    out
+}
+
+/// Reads a move in short notation for the player to move.
+///
+/// Long notation, castling and the single letter moves ("R", "T", "S", "#") are accepted too.
+pub fn from_short(board: &Board, variant: &Variant, text: &str) -> Result<Move, ShortError> {
+    let malformed = || ShortError::Malformed(text.to_owned());
+    let legal = legal_moves(board, variant);
+    // Long notation and castling always have a '-'; a single letter is never short notation
+    let long = text.parse::<Move>();
+    if text.contains('-') || text.len() == 1 {
+        let m = long.map_err(|_| malformed())?;
+        return match &m {
+            Move::Normal(_) | Move::KingCastle(_) | Move::QueenCastle(_)
+                if !is_legal(&legal, &m) =>
+            {
+                Err(ShortError::NoMatch(text.to_owned()))
+            }
+            _ => Ok(m),
+        };
+    }
+    // Long captures like "Nf3xe5" are also valid (but different) short notation
+    if let Ok(m) = long {
+        if is_legal(&legal, &m) {
+            return Ok(m);
+        }
+    }
+    let mateless = text.trim_end_matches('#');
+    let checkless = mateless.trim_end_matches('+');
+    let mates = text.len() - mateless.len();
+    let checks = mateless.len() - checkless.len();
+
+    let (body, promotion) = match checkless.find('=') {
+        Some(equals) => {
+            let mut promote = checkless[equals + 1..].chars();
+            match (promote.next(), promote.next()) {
+                (Some(p), None) => (&checkless[..equals], Some(p)),
+                _ => return Err(malformed()),
+            }
+        }
+        None => (checkless, None),
+    };
+    let (piece, body) = match body.chars().next() {
+        Some(c) if c.is_ascii_uppercase() => (c, &body[1..]),
+        Some(_) => ('P', body),
+        None => return Err(malformed()),
+    };
+    // The target square is the last letter and everything after it
+    let split = body
+        .rfind(|c: char| c.is_ascii_lowercase())
+        .ok_or_else(malformed)?;
+    let to: Position = body[split..].parse().map_err(|_| malformed())?;
+    let (from, capture) = match body[..split].strip_suffix('x') {
+        Some(from) => (from, true),
+        None => (&body[..split], false),
+    };
+    let from_file = from.chars().next().filter(|c| c.is_ascii_lowercase());
+    let from_rank = &from[from_file.map_or(0, |_| 1)..];
+    if !from_rank.chars().all(|c| c.is_ascii_digit()) {
+        return Err(malformed());
+    }
+
+    let mut found: Option<BasicMove> = None;
+    for m in legal {
+        let l = match m {
+            Move::Normal(l) => l,
+            _ => continue,
+        };
+        let (file, rank) = split_square(l.from);
+        let matches = l.piece == piece
+            && l.to == to
+            && from_file.map_or(true, |f| f == file)
+            && (from_rank.is_empty() || from_rank == rank)
+            && (!capture || l.captured.is_some())
+            && promotion.map_or(l.promotion.is_none(), |p| l.promotion == Some(p));
+        if !matches {
+            continue;
+        }
+        if found.is_some() {
+            return Err(ShortError::Ambiguous(text.to_owned()));
+        }
+        found = Some(l);
+    }
+    let found = found.ok_or_else(|| ShortError::NoMatch(text.to_owned()))?;
+    Ok(Move::Normal(BasicMove {
+        checks,
+        mates,
+        ..found
+    }))
+}
+
+/// Is `m` one of the `legal` moves, ignoring checks and mates
+fn is_legal(legal: &[Move], m: &Move) -> bool {
+    legal.iter().any(|l| match (l, m) {
+        (Move::Normal(l), Move::Normal(m)) => {
+            l.piece == m.piece
+                && l.from == m.from
+                && l.to == m.to
+                && l.captured == m.captured
+                && l.promotion == m.promotion
+        }
+        (Move::KingCastle(_), Move::KingCastle(_)) => true,
+        (Move::QueenCastle(_), Move::QueenCastle(_)) => true,
+        _ => false,
+    })
+}
+
+/// The file letter and rank number of a square
+fn split_square(pos: Position) -> (char, String) {
+    let name = pos.to_string();
+    let file = name.chars().next().unwrap();
+    (file, name[1..].to_owned())
+}
+
+impl Replay {
+    /// [`to_short`] for the current position
+    pub fn to_short(&self, m: &Move) -> String {
+        to_short(self.board(), self.variant(), m)
+    }
+    /// [`from_short`] for the current position
+    pub fn from_short(&self, text: &str) -> Result<Move, ShortError> {
+        from_short(self.board(), self.variant(), text)
+    }
+}
+
+/// Displays a game with its moves in short notation; made by [`PGN4::short_notation`]
+pub struct ShortNotation<'a> {
+    pgn: &'a PGN4,
+    variant: Variant,
+    letters: Option<&'a PieceLetters>,
+}
+
+impl<'a> ShortNotation<'a> {
+    /// Writes the piece letters with other letters
+    pub fn with_letters(self, letters: &'a PieceLetters) -> Self {
+        Self {
+            letters: Some(letters),
+            ..self
+        }
+    }
+}
+
+impl PGN4 {
+    /// Prepares to write the game with moves in short notation.
+    ///
+    /// All lines of the game need to be replayed for that, so this fails if any of them
+    /// can't be.
+    pub fn short_notation(&self) -> Result<ShortNotation<'_>, PositionError> {
+        let variant = self.variant()?;
+        check_line(&mut Replay::new(variant.clone()), &self.turns)?;
+        Ok(ShortNotation {
+            pgn: self,
+            variant,
+            letters: None,
+        })
+    }
+}
+
+fn check_line(replay: &mut Replay, turns: &[Turn]) -> Result<(), ReplayError> {
+    for qturn in turns.iter().flat_map(|t| &t.turns) {
+        for alt in &qturn.alternatives {
+            check_line(&mut replay.clone(), alt)?;
+        }
+        replay.play(qturn)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for ShortNotation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = WriteOptions {
+            letters: self.letters.cloned().unwrap_or_default(),
+            ..WriteOptions::chess_com()
+        };
+        let mut replay = Replay::new(self.variant.clone());
+        Writer::new(f, &options).pgn(self.pgn, Some(&mut replay))
+    }
+}
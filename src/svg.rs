@@ -0,0 +1,230 @@
+use crate::movegen::on_board;
+use crate::render::{label, view_square};
+use crate::replay::PositionError;
+use crate::types::*;
+use fen4::{Board, Color, Piece, Position, TurnColor};
+
+use std::fmt::Write;
+
+/// Choices for drawing a board as an SVG diagram
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SvgOptions {
+    /// Width and height of a square in pixels
+    pub square_size: u32,
+    /// Write the files and ranks next to the board
+    pub coordinates: bool,
+    /// The player whose side of the board is at the bottom
+    pub bottom: TurnColor,
+    /// Squares to highlight, like the ones of the last move
+    pub highlight: Vec<Position>,
+    /// Arrows to draw from the first square to the second
+    pub arrows: Vec<(Position, Position)>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            square_size: 40,
+            coordinates: true,
+            bottom: TurnColor::Red,
+            highlight: Vec::new(),
+            arrows: Vec::new(),
+        }
+    }
+}
+
+pub(crate) const LIGHT: &str = "#f0d9b5";
+pub(crate) const DARK: &str = "#b58863";
+pub(crate) const WALL: &str = "#555555";
+pub(crate) const HIGHLIGHT: &str = "#f7ec59";
+const ARROW: &str = "#e8740c";
+
+/// The fill color for pieces of `color`
+pub(crate) fn piece_color(color: Color) -> &'static str {
+    match color {
+        Color::Turn(TurnColor::Red) => "#bf3b43",
+        Color::Turn(TurnColor::Blue) => "#4185bf",
+        Color::Turn(TurnColor::Yellow) => "#c09526",
+        Color::Turn(TurnColor::Green) => "#4e9161",
+        Color::Dead(_) => "#9e9e9e",
+    }
+}
+
+/// Where squares are drawn for some [`SvgOptions`]
+struct Layout {
+    size: u32,
+    /// Space on the left and bottom for coordinates
+    margin: u32,
+    bottom: TurnColor,
+}
+
+impl Layout {
+    /// The top left corner of the square at (`x`, `y`) counting from the bottom left
+    fn corner(&self, x: usize, y: usize) -> (u32, u32) {
+        (
+            self.margin + x as u32 * self.size,
+            (13 - y as u32) * self.size,
+        )
+    }
+    /// The center of `pos`
+    fn center(&self, pos: Position) -> (u32, u32) {
+        let (x, y) = (0..14)
+            .flat_map(|x| (0..14).map(move |y| (x, y)))
+            .find(|(x, y)| view_square(self.bottom, *x, *y) == pos)
+            .unwrap_or((0, 0));
+        let (left, top) = self.corner(x, y);
+        (left + self.size / 2, top + self.size / 2)
+    }
+}
+
+/// Escapes text for use in XML
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Draws the board as an SVG image. Pieces are discs in their player's color with their
+/// letter on them; dead pieces are grey.
+///
+/// The same board and options always give the same text.
+pub fn render_svg(board: &Board, options: &SvgOptions) -> String {
+    let size = options.square_size;
+    let layout = Layout {
+        size,
+        margin: if options.coordinates { size / 2 } else { 0 },
+        bottom: options.bottom,
+    };
+    let full = 14 * size + layout.margin;
+    let mut out = String::new();
+    write_svg(&mut out, board, options, &layout, full).expect("Writing to a String doesn't fail");
+    out
+}
+
+fn write_svg(
+    out: &mut String,
+    board: &Board,
+    options: &SvgOptions,
+    layout: &Layout,
+    full: u32,
+) -> std::fmt::Result {
+    let size = layout.size;
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+        full
+    )?;
+    if !options.arrows.is_empty() {
+        writeln!(
+            out,
+            r#"<defs><marker id="arrowhead" viewBox="0 0 10 10" refX="5" refY="5" markerWidth="4" markerHeight="4" orient="auto"><path d="M0,0 L10,5 L0,10 z" fill="{}"/></marker></defs>"#,
+            ARROW
+        )?;
+    }
+    for y in (0..14).rev() {
+        for x in 0..14 {
+            let pos = view_square(options.bottom, x, y);
+            if !on_board(pos.row as isize, pos.col as isize) {
+                continue;
+            }
+            let (left, top) = layout.corner(x, y);
+            let piece = &board.board[pos.row][pos.col];
+            let fill = if *piece == Piece::Wall {
+                WALL
+            } else if options.highlight.contains(&pos) {
+                HIGHLIGHT
+            } else if (pos.row + pos.col) % 2 == 0 {
+                DARK
+            } else {
+                LIGHT
+            };
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                left, top, size, size, fill
+            )?;
+            if let Piece::Normal(color, shape) = piece {
+                let (cx, cy) = (left + size / 2, top + size / 2);
+                writeln!(
+                    out,
+                    r##"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="#222222" stroke-width="1"/>"##,
+                    cx,
+                    cy,
+                    size * 2 / 5,
+                    piece_color(*color)
+                )?;
+                writeln!(
+                    out,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" font-weight="bold" text-anchor="middle" dominant-baseline="central" fill="white">{}</text>"#,
+                    cx,
+                    cy,
+                    size / 2,
+                    escape(&shape.to_string())
+                )?;
+            }
+        }
+    }
+    if options.coordinates {
+        let font = size / 3;
+        for y in 0..14 {
+            let rank = label(
+                view_square(options.bottom, 0, y),
+                view_square(options.bottom, 13, y),
+            );
+            let (_, top) = layout.corner(0, y);
+            writeln!(
+                out,
+                r##"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central" fill="#333333">{}</text>"##,
+                layout.margin / 2,
+                top + size / 2,
+                font,
+                rank
+            )?;
+        }
+        for x in 0..14 {
+            let file = label(
+                view_square(options.bottom, x, 0),
+                view_square(options.bottom, x, 13),
+            );
+            let (left, _) = layout.corner(x, 0);
+            writeln!(
+                out,
+                r##"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central" fill="#333333">{}</text>"##,
+                left + size / 2,
+                14 * size + layout.margin / 2,
+                font,
+                file
+            )?;
+        }
+    }
+    for (from, to) in &options.arrows {
+        let (x1, y1) = layout.center(*from);
+        let (x2, y2) = layout.center(*to);
+        writeln!(
+            out,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-opacity="0.8" marker-end="url(#arrowhead)"/>"#,
+            x1,
+            y1,
+            x2,
+            y2,
+            ARROW,
+            size / 6
+        )?;
+    }
+    writeln!(out, "</svg>")
+}
+
+impl PGN4 {
+    /// Draws the position after following `path`, highlighting the last move
+    pub fn render_svg_at(
+        &self,
+        path: &[usize],
+        options: &SvgOptions,
+    ) -> Result<String, PositionError> {
+        let (board, last) = self.position_at(path)?;
+        let mut options = options.clone();
+        options.highlight.extend(last);
+        Ok(render_svg(&board, &options))
+    }
+}
@@ -1,3 +1,4 @@
+use crate::path::check;
 use crate::*;
 use fen4::{Board, Color, TurnColor};
 use std::num::ParseIntError;
@@ -25,6 +26,32 @@ pub enum VariantError {
     Other,
 }
 
+/// Possible errors while editing the moves of a pgn4
+#[derive(Error, PartialEq, Debug, Clone)]
+pub enum EditError {
+    #[error("Path given has an even number of numbers")]
+    EvenPath,
+    #[error("A zero was found in the path. Only valid way to have a zero in the path is [0].")]
+    ZeroInPath,
+    #[error("The path goes past the end of a line")]
+    PastEnd,
+    #[error("The alternative in the path is not present on its qturn")]
+    MissingAlternative,
+    #[error("The start of the game can't be promoted or deleted")]
+    StartOfGame,
+}
+
+impl From<VisitingError> for EditError {
+    fn from(error: VisitingError) -> Self {
+        match error {
+            VisitingError::EvenPath => EditError::EvenPath,
+            VisitingError::ZeroInPath => EditError::ZeroInPath,
+            VisitingError::InvalidAlternative => EditError::MissingAlternative,
+            _ => EditError::PastEnd,
+        }
+    }
+}
+
 impl PGN4 {
     /// Searches for a tag and optionally returns a reference to it if found
     pub fn tag<'a>(&'a self, tag_name: &'_ str) -> Option<&'a str> {
@@ -49,10 +76,7 @@ impl PGN4 {
 
         let custom_board = start_fen.is_some();
         if let Some(fen) = start_fen {
-            base.initial_board = fen.parse().map_err(|e| {
-                eprintln!("failed to parse fen4 because or error {:?}", e);
-                InvalidFen4(e)
-            })?;
+            base.initial_board = fen.parse().map_err(InvalidFen4)?;
         }
         if let Some(tmp) = rule_variants {
             for rule in tmp.split(' ') {
@@ -95,8 +119,10 @@ impl PGN4 {
                     let count = iter.next().unwrap();
                     let int_count: usize = count.parse().map_err(|ie| BadInt(ie))?;
                     base.ncheck = int_count;
-                    // TODO: reject if not None or equal
-                    base.initial_board.extra_options.lives = Some([int_count; 4]);
+                    // A custom position may already be partway through the game
+                    if base.initial_board.extra_options.lives.is_none() {
+                        base.initial_board.extra_options.lives = Some([int_count; 4]);
+                    }
                 } else {
                     *(match rule {
                         "EnPassant" => &mut base.en_passant,
@@ -202,12 +228,9 @@ impl PGN4 {
     /// Format for path is `[ply forward] : [alternative index, plyforward]*`
     /// In order to follow it, move to the next move `path[0]` times, then if
     /// there is more to path, go into alternative `path[1]-1` and continue with `path[2..]`
-    /// This is the format Chess.com uses to notate with
-    pub fn append_move(&mut self, path: &[usize], q: QuarterTurn) -> Result<usize, ()> {
-        if path.len() % 2 == 0 {
-            eprintln!("Invalid path length");
-            return Err(());
-        }
+    /// This is the format Chess.com uses to notate with; a [`Path`] always follows it.
+    pub fn append_move(&mut self, path: &[usize], q: QuarterTurn) -> Result<usize, EditError> {
+        check(path)?;
         if path.len() == 1 && path[0] == 0 && self.turns.len() == 0 {
             self.turns.push(Turn {
                 number: 1,
@@ -221,12 +244,8 @@ impl PGN4 {
             path: &[usize],
             q: QuarterTurn,
             mut total: usize,
-        ) -> Result<usize, ()> {
+        ) -> Result<usize, EditError> {
             let ply = path[0];
-            if ply == 0 && path.len() > 2 {
-                eprintln!("E1");
-                return Err(());
-            }
             let mut current = 0;
             for turn in turns.iter_mut() {
                 for qturn in &mut turn.turns {
@@ -235,8 +254,7 @@ impl PGN4 {
                         if current == ply {
                             let alt = path[1];
                             if alt == 0 || qturn.alternatives.len() < alt {
-                                eprintln!("E2");
-                                return Err(());
+                                return Err(EditError::MissingAlternative);
                             } else {
                                 return helper(
                                     &mut qturn.alternatives[alt - 1],
@@ -285,22 +303,20 @@ impl PGN4 {
                 } else {
                     turns[last].turns.push(q);
                 }
-                return Ok(0);
+                Ok(0)
             } else {
-                return Err(());
+                Err(EditError::PastEnd)
             }
         }
         helper(&mut self.turns, path, q, 0)
     }
     /// Switches the order of alternatives / main moves such that the given path ends up as the mainline
-    pub fn promote_to_mainline(&mut self, path: &[usize]) -> Result<(), ()> {
-        if path.len() % 2 == 0 {
-            return Err(());
-        }
-        fn helper(turns: &mut Vec<Turn>, path: &[usize]) -> Result<(), ()> {
+    pub fn promote_to_mainline(&mut self, path: &[usize]) -> Result<(), EditError> {
+        check(path)?;
+        fn helper(turns: &mut Vec<Turn>, path: &[usize]) -> Result<(), EditError> {
             let ply = path[0];
             if ply == 0 {
-                return Err(());
+                return Err(EditError::StartOfGame);
             }
             if path.len() == 1 {
                 return Ok(());
@@ -316,8 +332,7 @@ impl PGN4 {
                         let mut to_mainline = {
                             let alternatives = &mut turns[i].turns[j].alternatives;
                             if alt == 0 || alternatives.len() < alt {
-                                eprintln!("E2");
-                                return Err(());
+                                return Err(EditError::MissingAlternative);
                             } else {
                                 helper(&mut alternatives[alt - 1], &path[2..])?;
                                 alternatives.remove(alt - 1)
@@ -356,19 +371,17 @@ impl PGN4 {
                     }
                 }
             }
-            return Err(());
+            Err(EditError::PastEnd)
         }
         helper(&mut self.turns, path)
     }
     /// Deletes all further moves after path and path istself. Any alternatives to it remain.
-    pub fn delete_from(&mut self, path: &[usize]) -> Result<(), ()> {
-        if path.len() % 2 == 0 {
-            return Err(());
-        }
-        fn helper(turns: &mut Vec<Turn>, path: &[usize]) -> Result<(), ()> {
+    pub fn delete_from(&mut self, path: &[usize]) -> Result<(), EditError> {
+        check(path)?;
+        fn helper(turns: &mut Vec<Turn>, path: &[usize]) -> Result<(), EditError> {
             let ply = path[0];
             if ply == 0 {
-                return Err(());
+                return Err(EditError::StartOfGame);
             }
             let mut current = 0;
             let t_len = turns.len();
@@ -381,7 +394,7 @@ impl PGN4 {
                             let alt = path[1];
                             let alternatives = &mut turns[i].turns[j].alternatives;
                             if alt == 0 || alternatives.len() < alt {
-                                return Err(());
+                                return Err(EditError::MissingAlternative);
                             } else {
                                 helper(&mut alternatives[alt - 1], &path[2..])?;
                                 // If the deletion makes an alternative empty, delete it
@@ -423,12 +436,30 @@ impl PGN4 {
                     }
                 }
             }
-            return Err(());
+            Err(EditError::PastEnd)
         }
         helper(&mut self.turns, path)
     }
 }
 
+impl QuarterTurn {
+    /// The move as written in a pgn4 without its description or alternatives, e.g. "Kh1-i2R"
+    pub fn move_text(&self) -> String {
+        self.move_text_with(&self.main.to_string())
+    }
+    /// [`move_text`](Self::move_text) with `main` written in place of the main move
+    pub(crate) fn move_text_with(&self, main: &str) -> String {
+        let mut text = main.to_owned();
+        if let Some(modifier) = &self.modifier {
+            text.push_str(&modifier.to_string());
+        }
+        if self.extra_stalemate {
+            text.push('S');
+        }
+        text
+    }
+}
+
 impl Variant {
     /// The default variant for the team gamemode.
     pub fn team_default() -> Self {
@@ -495,6 +526,37 @@ impl Variant {
         }
         false
     }
+    /// Returns true if the variant is played in teams rather than free for all
+    pub fn teams(&self) -> bool {
+        matches!(self.red_teammate, Color::Turn(_))
+    }
+    /// The teammate of a player, if playing in teams
+    pub fn teammate(&self, color: TurnColor) -> Option<TurnColor> {
+        use TurnColor::*;
+        let red_teammate = match self.red_teammate {
+            Color::Turn(c) => c,
+            Color::Dead(_) => return None,
+        };
+        if color == Red {
+            Some(red_teammate)
+        } else if color == red_teammate {
+            Some(Red)
+        } else {
+            [Blue, Yellow, Green]
+                .iter()
+                .copied()
+                .find(|c| *c != color && *c != red_teammate)
+        }
+    }
+    /// The players on the same side as a player (including themselves) in turn order
+    pub fn team(&self, color: TurnColor) -> Vec<TurnColor> {
+        let mut out = vec![color];
+        if let Some(teammate) = self.teammate(color) {
+            out.push(teammate);
+            out.sort();
+        }
+        out
+    }
     /// Distance of the pans base rank from the edge of the board.
     pub fn pawn_base_rank(&self) -> usize {
         self.initial_board.extra_options.pawnbaserank
@@ -0,0 +1,257 @@
+use crate::path::Path;
+use crate::replay::Replay;
+use crate::types::*;
+use fen4::TurnColor;
+use std::slice;
+
+/// Which moves [`PGN4::moves`] and [`PGN4::moves_mut`] visit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MoveFilter {
+    /// Every move in the game
+    All,
+    /// Only moves of the mainline; variations are not looked at
+    Mainline,
+    /// Only moves inside of variations, including nested ones
+    Variations,
+}
+
+/// Where a move is in the game
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MoveInfo {
+    /// The path to the position after the move
+    pub path: Path,
+    /// How many variations deep the move is; 0 for the mainline
+    pub depth: usize,
+    /// How many quarterturns from the start of the game the move is, counting from 1
+    pub ply: usize,
+    /// The player making the move, or `None` if the game couldn't be replayed up to it
+    pub side: Option<TurnColor>,
+}
+
+/// Mutable access to a quarterturn without its alternatives, which are visited on their own
+#[derive(Debug)]
+pub struct QuarterTurnMut<'a> {
+    pub main: &'a mut Move,
+    pub modifier: &'a mut Option<Move>,
+    pub extra_stalemate: &'a mut bool,
+    pub description: &'a mut Option<String>,
+}
+
+/// Iterator over the moves of a game in pre-order, made by [`PGN4::moves`]
+#[derive(Debug)]
+pub struct Moves<'a> {
+    stack: Vec<Frame<slice::Iter<'a, Turn>, slice::Iter<'a, QuarterTurn>>>,
+    filter: MoveFilter,
+}
+
+/// Iterator over the moves of a game in pre-order, made by [`PGN4::moves_mut`]
+#[derive(Debug)]
+pub struct MovesMut<'a> {
+    stack: Vec<Frame<slice::IterMut<'a, Turn>, slice::IterMut<'a, QuarterTurn>>>,
+    filter: MoveFilter,
+}
+
+impl MoveFilter {
+    fn includes(self, depth: usize) -> bool {
+        match self {
+            MoveFilter::All => true,
+            MoveFilter::Mainline => depth == 0,
+            MoveFilter::Variations => depth > 0,
+        }
+    }
+}
+
+/// A line that is partway through being visited
+#[derive(Debug)]
+struct Frame<T, Q> {
+    /// The path to the line, empty for the mainline
+    base: Vec<usize>,
+    /// Turns of the line after the current one
+    turns: T,
+    /// Quarterturns of the current turn not visited yet
+    qturns: Q,
+    /// Quarterturns of the line visited so far
+    index: usize,
+    /// Quarterturns played before the line starts
+    before: usize,
+    /// The position before the next quarterturn, or `None` once the line can't be replayed
+    replay: Option<Replay>,
+}
+
+impl<T, Q> Frame<T, Q> {
+    fn new(base: Vec<usize>, turns: T, qturns: Q, before: usize, replay: Option<Replay>) -> Self {
+        Frame {
+            base,
+            turns,
+            qturns,
+            index: 0,
+            before,
+            replay,
+        }
+    }
+    /// Where the quarterturn just visited is
+    fn info(&self) -> MoveInfo {
+        let mut path = self.base.clone();
+        path.push(self.index);
+        MoveInfo {
+            path: Path(path),
+            depth: self.base.len() / 2,
+            ply: self.before + self.index,
+            side: self.replay.as_ref().map(|r| r.board().turn),
+        }
+    }
+    /// Plays the quarterturn just visited, giving back the position before it if its
+    /// alternatives need one
+    fn play(&mut self, qturn: &QuarterTurn, branching: bool) -> Option<Replay> {
+        let before = if branching && !qturn.alternatives.is_empty() {
+            self.replay.clone()
+        } else {
+            None
+        };
+        if let Some(replay) = &mut self.replay {
+            if replay.play(qturn).is_err() {
+                self.replay = None;
+            }
+        }
+        before
+    }
+}
+
+impl<'a> Frame<slice::Iter<'a, Turn>, slice::Iter<'a, QuarterTurn>> {
+    fn of(base: Vec<usize>, turns: &'a [Turn], before: usize, replay: Option<Replay>) -> Self {
+        Frame::new(base, turns.iter(), [].iter(), before, replay)
+    }
+    fn next_qturn(&mut self) -> Option<&'a QuarterTurn> {
+        loop {
+            if let Some(qturn) = self.qturns.next() {
+                self.index += 1;
+                return Some(qturn);
+            }
+            self.qturns = self.turns.next()?.turns.iter();
+        }
+    }
+}
+
+impl<'a> Frame<slice::IterMut<'a, Turn>, slice::IterMut<'a, QuarterTurn>> {
+    fn of_mut(
+        base: Vec<usize>,
+        turns: &'a mut [Turn],
+        before: usize,
+        replay: Option<Replay>,
+    ) -> Self {
+        Frame::new(base, turns.iter_mut(), [].iter_mut(), before, replay)
+    }
+    fn next_qturn(&mut self) -> Option<&'a mut QuarterTurn> {
+        loop {
+            if let Some(qturn) = self.qturns.next() {
+                self.index += 1;
+                return Some(qturn);
+            }
+            self.qturns = self.turns.next()?.turns.iter_mut();
+        }
+    }
+}
+
+impl<'a> Iterator for Moves<'a> {
+    type Item = (MoveInfo, &'a QuarterTurn);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            let qturn = match frame.next_qturn() {
+                Some(qturn) => qturn,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            let info = frame.info();
+            let branch = frame.play(qturn, self.filter != MoveFilter::Mainline);
+            if self.filter != MoveFilter::Mainline {
+                // Pushed last to first so the first alternative is visited first
+                for (a, alt) in qturn.alternatives.iter().enumerate().rev() {
+                    let mut base = info.path.to_vec();
+                    base.push(a + 1);
+                    self.stack
+                        .push(Frame::of(base, alt, info.ply - 1, branch.clone()));
+                }
+            }
+            if self.filter.includes(info.depth) {
+                return Some((info, qturn));
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for MovesMut<'a> {
+    type Item = (MoveInfo, QuarterTurnMut<'a>);
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            let qturn = match frame.next_qturn() {
+                Some(qturn) => qturn,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            let info = frame.info();
+            let branch = frame.play(qturn, self.filter != MoveFilter::Mainline);
+            let QuarterTurn {
+                main,
+                modifier,
+                extra_stalemate,
+                description,
+                alternatives,
+            } = qturn;
+            if self.filter != MoveFilter::Mainline {
+                for (a, alt) in alternatives.iter_mut().enumerate().rev() {
+                    let mut base = info.path.to_vec();
+                    base.push(a + 1);
+                    self.stack
+                        .push(Frame::of_mut(base, alt, info.ply - 1, branch.clone()));
+                }
+            }
+            if self.filter.includes(info.depth) {
+                return Some((
+                    info,
+                    QuarterTurnMut {
+                        main,
+                        modifier,
+                        extra_stalemate,
+                        description,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+fn start(pgn: &PGN4) -> Option<Replay> {
+    pgn.variant().ok().map(Replay::new)
+}
+
+impl PGN4 {
+    /// Every move of the game in the order they are written: each move comes before its
+    /// alternatives, which come before the rest of the line.
+    ///
+    /// Moves are found as the iterator goes, without walking the rest of the game. Each line
+    /// is replayed along the way to find who makes each move; if the tags have no known
+    /// variant or a line has an illegal move, [`MoveInfo::side`] is `None` from there on.
+    pub fn moves(&self, filter: MoveFilter) -> Moves<'_> {
+        Moves {
+            stack: vec![Frame::of(Vec::new(), &self.turns, 0, start(self))],
+            filter,
+        }
+    }
+    /// [`moves`](PGN4::moves) with the ability to change each move.
+    ///
+    /// Each move is played before it is handed out, so changing it doesn't change the
+    /// sides found after it.
+    pub fn moves_mut(&mut self, filter: MoveFilter) -> MovesMut<'_> {
+        let replay = start(self);
+        MovesMut {
+            stack: vec![Frame::of_mut(Vec::new(), &mut self.turns, 0, replay)],
+            filter,
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use fen4::{Board, Color, Position};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Any move e.g. Ka4-b5, T, O-O, ...
 ///
 /// Castling has a strange property that checks are not notated.
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Move {
     Checkmate,
     Timeout,
@@ -16,6 +19,7 @@ pub enum Move {
 
 /// Parsed version of the PGN4 tag "Result"
 #[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GameResult {
     Error,
     Aborted,
@@ -30,10 +34,13 @@ pub enum GameResult {
 /// The mapping from Struct to String is mostly straightforward. The struct elements are in the order they are serialized in.
 /// If the piece moved is a pawn, `piece` = 'P'. Similarly, is a pawn is captured, `captured` = Some('P').
 #[derive(PartialEq, Eq, Hash, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BasicMove {
     pub piece: char,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::position"))]
     pub from: Position,
     pub captured: Option<char>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::position"))]
     pub to: Position,
     pub promotion: Option<char>,
     pub checks: usize,
@@ -46,8 +53,11 @@ pub struct BasicMove {
 ///
 /// Notably, Chess.com does not support variations within variations whereas this
 /// tool does. If you try to import a pgn4 with recursive variations into Chess.com,
-/// you will get errors.
+/// you will get errors. [`PGN4::flatten_variations`] removes them before uploading.
+///
+/// [`GameTree`](crate::GameTree) holds the same moves as nodes linked to the moves around them.
 #[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PGN4 {
     /// PGN4 files have a list of key value pairs in the beginning that specify variants, time control, and other metadata
     pub bracketed: Vec<(String, String)>,
@@ -62,6 +72,7 @@ pub struct PGN4 {
 
 /// Representation of a single Turn
 #[derive(PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Turn {
     /// The number that is shown at the beginning of a turn. 0 if no number should be shown.
     pub number: usize,
@@ -85,6 +96,7 @@ pub struct Turn {
 /// is not just a normal king move. Alternatively it is possible to be in checkmate
 /// following your pieces dying. That is notated like "R#".
 #[derive(PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct QuarterTurn {
     pub main: Move,
     /// If FFA with zombies, it is possible to resign / timeout and still move.
@@ -102,9 +114,12 @@ pub struct QuarterTurn {
 ///
 /// This has been made to closely resemble the setup for starting games on Chess.com.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Variant {
     // critical options
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::color"))]
     pub red_teammate: Color,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialize::board"))]
     pub initial_board: Board,
 
     // general options
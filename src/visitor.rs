@@ -1,3 +1,4 @@
+use crate::path::check;
 use crate::*;
 use std::fmt;
 
@@ -48,6 +49,10 @@ pub enum VisitingError {
     EvenPath,
     #[error("You tried to look one past the end of the game; stop here.")]
     UnexpectedEndOfGame,
+    #[error("You tried to look before the start of the game")]
+    UnexpectedStartOfGame,
+    #[error("The visitor is in the mainline, which has no parent")]
+    InMainline,
     #[error("A zero was found in the path. Only valid way to have a zero in the path is [0].")]
     ZeroInPath,
     #[error("There was an empty turn in the pgn")]
@@ -63,26 +68,23 @@ pub enum VisitingError {
 /// A view into the quarterturns of a pgn4.
 #[derive(Debug, Clone)]
 pub struct Visitor<'a> {
-    turns: &'a Vec<Turn>,
-    i: usize,
-    j: usize,
+    root: &'a Vec<Turn>,
+    cursor: Cursor,
 }
 
 /// A mutable view into the quarterturns of a pgn4.
 #[derive(Debug)]
 pub struct VisitorMut<'a> {
-    turns: &'a mut Vec<Turn>,
-    i: usize,
-    j: usize,
+    root: &'a mut Vec<Turn>,
+    cursor: Cursor,
 }
 
 impl<'a> Visitor<'a> {
     /// Creates a new Visitor
     pub fn new(pgn4: &'a PGN4) -> Self {
         Self {
-            turns: &pgn4.turns,
-            i: 0,
-            j: usize::MAX,
+            root: &pgn4.turns,
+            cursor: Cursor::start(),
         }
     }
 }
@@ -91,29 +93,204 @@ impl<'a> VisitorMut<'a> {
     /// Creates a new VisitorMut
     pub fn new(pgn4: &'a mut PGN4) -> Self {
         Self {
-            turns: &mut pgn4.turns,
-            i: 0,
-            j: usize::MAX,
+            root: &mut pgn4.turns,
+            cursor: Cursor::start(),
         }
     }
-    /// Reborrows VisitorMut because it cannot be cloned. By keeping a trail of reborrows, it is possible to backtrack with VisitorMut
-    ///
-    /// TODO: example of usefullness
+    /// Reborrows VisitorMut because it cannot be cloned. This allows looking ahead without
+    /// giving up the original visitor.
     pub fn reborrow<'b>(&'b mut self) -> VisitorMut<'b> {
         VisitorMut {
-            turns: &mut self.turns,
-            i: self.i,
-            j: self.j,
+            root: self.root,
+            cursor: self.cursor.clone(),
         }
     }
     /// View the currently hovered QuarterTurn if it exists
     pub fn qturn_mut<'b>(&'b mut self) -> Option<&'b mut QuarterTurn> {
+        if self.cursor.j == usize::MAX {
+            None
+        } else {
+            let (i, j) = (self.cursor.i, self.cursor.j);
+            Some(&mut self.cursor.line_mut(self.root)[i].turns[j])
+        }
+    }
+}
+
+/// How many quarterturns into `turns` the position `i`, `j` is
+fn line_ply(turns: &[Turn], i: usize, j: usize) -> usize {
+    if j == usize::MAX {
+        0
+    } else {
+        turns[..i].iter().map(|t| t.turns.len()).sum::<usize>() + j + 1
+    }
+}
+
+/// The indices of the quarterturn `ply` quarterturns into `turns`, if there are that many
+fn locate(turns: &[Turn], ply: usize) -> Option<(usize, usize)> {
+    if ply == 0 {
+        return Some((0, usize::MAX));
+    }
+    let mut current = 0;
+    for (i, turn) in turns.iter().enumerate() {
+        if ply <= current + turn.turns.len() {
+            return Some((i, ply - current - 1));
+        }
+        current += turn.turns.len();
+    }
+    None
+}
+
+fn visitor_path(base: &[usize], ply: usize) -> Path {
+    let mut path = base.to_vec();
+    if ply == 0 && !path.is_empty() {
+        path.push(1);
+        let mut path = Path(path);
+        path.pop_ply();
+        path
+    } else {
+        path.push(ply);
+        Path(path)
+    }
+}
+
+/// Where a visitor is, shared by Visitor and VisitorMut.
+///
+/// Only the way to the current line is stored, not the line itself, so a VisitorMut can go
+/// back out of a variation without holding on to two mutable borrows.
+#[derive(Debug, Clone)]
+struct Cursor {
+    /// The `[ply, alternative]` pairs leading to the current line
+    base: Vec<usize>,
+    /// The hovered quarterturn is `line[i].turns[j]`; `j` is `usize::MAX` before the first
+    i: usize,
+    j: usize,
+}
+
+impl Cursor {
+    fn start() -> Self {
+        Self {
+            base: Vec::new(),
+            i: 0,
+            j: usize::MAX,
+        }
+    }
+    fn line<'t>(&self, root: &'t Vec<Turn>) -> &'t Vec<Turn> {
+        let mut turns = root;
+        for pair in self.base.chunks(2) {
+            let (i, j) = locate(turns, pair[0]).expect("The base was checked when entering");
+            turns = &turns[i].turns[j].alternatives[pair[1] - 1];
+        }
+        turns
+    }
+    fn line_mut<'t>(&self, root: &'t mut Vec<Turn>) -> &'t mut Vec<Turn> {
+        let mut turns = root;
+        for pair in self.base.chunks(2) {
+            let (i, j) = locate(turns, pair[0]).expect("The base was checked when entering");
+            turns = &mut turns[i].turns[j].alternatives[pair[1] - 1];
+        }
+        turns
+    }
+    fn last(&self, root: &Vec<Turn>) -> bool {
+        let turns = self.line(root);
+        (self.i == 0 && self.j == usize::MAX && turns.is_empty())
+            || (self.i == turns.len() - 1 && self.j == turns[self.i].turns.len() - 1)
+    }
+    fn qturn<'t>(&self, root: &'t Vec<Turn>) -> Option<&'t QuarterTurn> {
         if self.j == usize::MAX {
             None
         } else {
-            Some(&mut self.turns[self.i].turns[self.j])
+            Some(&self.line(root)[self.i].turns[self.j])
         }
     }
+    fn alternatives(&self, root: &Vec<Turn>) -> usize {
+        self.qturn(root).map_or(0, |q| q.alternatives.len())
+    }
+    fn path(&self, root: &Vec<Turn>) -> Path {
+        visitor_path(&self.base, line_ply(self.line(root), self.i, self.j))
+    }
+    fn next(&mut self, root: &Vec<Turn>) -> Result<(), VisitingError> {
+        let turns = self.line(root);
+        let i = self.i;
+        let j = self.j;
+        if i == 0 && j == usize::MAX {
+            // special case for the beginning of line
+            return if turns.is_empty() {
+                Err(VisitingError::UnexpectedEndOfGame)
+            } else if turns[i].turns.is_empty() {
+                Err(VisitingError::EmptyTurn)
+            } else {
+                self.j = 0;
+                Ok(())
+            };
+        }
+        let len = turns[i].turns.len();
+        if j >= len {
+            Err(VisitingError::Internal)
+        } else if j + 1 == len {
+            if i + 1 == turns.len() {
+                Err(VisitingError::UnexpectedEndOfGame)
+            } else if turns[i + 1].turns.is_empty() {
+                Err(VisitingError::EmptyTurn)
+            } else {
+                self.j = 0;
+                self.i += 1;
+                Ok(())
+            }
+        } else {
+            self.j += 1;
+            Ok(())
+        }
+    }
+    fn enter_alternative(&mut self, root: &Vec<Turn>, alt: usize) -> Result<(), VisitingError> {
+        if alt == 0 || self.alternatives(root) < alt {
+            return Err(VisitingError::InvalidAlternative);
+        }
+        let ply = line_ply(self.line(root), self.i, self.j);
+        self.base.extend(&[ply, alt]);
+        self.i = 0;
+        self.j = usize::MAX;
+        Ok(())
+    }
+    fn prev(&mut self, root: &Vec<Turn>) -> Result<(), VisitingError> {
+        let mut path = self.path(root);
+        if !path.pop_ply() {
+            return Err(VisitingError::UnexpectedStartOfGame);
+        }
+        let (ply, base) = path.split_last().expect("Paths are never empty");
+        self.base = base.to_vec();
+        let (i, j) = locate(self.line(root), *ply).ok_or(VisitingError::Internal)?;
+        self.i = i;
+        self.j = j;
+        Ok(())
+    }
+    fn parent(&mut self, root: &Vec<Turn>) -> Result<(), VisitingError> {
+        let len = self.base.len();
+        if len == 0 {
+            return Err(VisitingError::InMainline);
+        }
+        let ply = self.base[len - 2];
+        self.base.truncate(len - 2);
+        let (i, j) = locate(self.line(root), ply).ok_or(VisitingError::Internal)?;
+        self.i = i;
+        self.j = j;
+        Ok(())
+    }
+    fn first(&mut self) {
+        self.i = 0;
+        self.j = usize::MAX;
+    }
+    fn end(&mut self, root: &Vec<Turn>) -> Result<(), VisitingError> {
+        let turns = self.line(root);
+        match turns.last() {
+            None => self.first(),
+            Some(turn) if turn.turns.is_empty() => return Err(VisitingError::EmptyTurn),
+            Some(turn) => {
+                self.i = turns.len() - 1;
+                self.j = turn.turns.len() - 1;
+            }
+        }
+        Ok(())
+    }
 }
 
 /// All common methods between Visitor and VisitorMut
@@ -127,10 +304,26 @@ where
     fn alternatives(&self) -> usize;
     /// View the currently hovered QuarterTurn if it exists
     fn qturn(&self) -> Option<&QuarterTurn>;
+    /// Where the visitor is, in the format the editing methods like
+    /// [`append_move`](PGN4::append_move) take.
+    ///
+    /// At the start of an alternative (before hovering its first quarterturn) this is the
+    /// path of the quarterturn before the one it replaces.
+    fn path(&self) -> Path;
     /// Move the visitor one forward
     fn next(self) -> Result<Self, VisitingError>;
     /// Move the visitor into one of the current qturns alternatives. Leaves the visitor at the start of the line without any hovered quarterturn.
     fn into_alternative(self, alt: usize) -> Result<Self, VisitingError>;
+    /// Move the visitor one back. Going back from the first quarterturn of an alternative
+    /// leaves it, so this always ends up one quarterturn before [`path`](Self::path).
+    fn prev(self) -> Result<Self, VisitingError>;
+    /// Leave the current alternative, hovering the quarterturn it is an alternative to
+    fn parent(self) -> Result<Self, VisitingError>;
+    /// Move the visitor to the start of the current line without any hovered quarterturn
+    fn first(self) -> Self;
+    /// Move the visitor to the last quarterturn of the current line, after which
+    /// [`last`](Self::last) is true
+    fn end(self) -> Result<Self, VisitingError>;
     /// Follow one step of a path.
     fn follow_once<'a>(
         mut self,
@@ -179,17 +372,10 @@ where
         partial: &'_ mut PartialPath<'a>,
         path: &'a [usize],
     ) -> Result<Self, VisitingError> {
-        if path.len() % 2 == 0 {
-            return Err(VisitingError::EvenPath);
-        }
-        if path == &[0] {
+        check(path)?;
+        if path == [0] {
             return Ok(self);
         }
-        for val in path {
-            if *val == 0 {
-                return Err(VisitingError::ZeroInPath);
-            }
-        }
         while !partial.done(path) {
             self = self.follow_once(partial, path)?;
         }
@@ -199,134 +385,80 @@ where
 
 impl<'a> VisitorCommon for Visitor<'a> {
     fn last(&self) -> bool {
-        (self.i == 0 && self.j == usize::MAX && self.turns.len() == 0)
-            || (self.i == self.turns.len() - 1 && self.j == self.turns[self.i].turns.len() - 1)
+        self.cursor.last(self.root)
     }
     fn alternatives(&self) -> usize {
-        if self.j == usize::MAX {
-            0
-        } else {
-            self.turns[self.i].turns[self.j].alternatives.len()
-        }
+        self.cursor.alternatives(self.root)
     }
     fn qturn(&self) -> Option<&QuarterTurn> {
-        if self.j == usize::MAX {
-            None
-        } else {
-            Some(&self.turns[self.i].turns[self.j])
-        }
+        self.cursor.qturn(self.root)
+    }
+    fn path(&self) -> Path {
+        self.cursor.path(self.root)
     }
 
     fn next(mut self) -> Result<Self, VisitingError> {
-        let i = self.i;
-        let j = self.j;
-        if i == 0 && j == usize::MAX {
-            // special case for the beginning of line
-            return if self.turns.len() == 0 {
-                Err(VisitingError::UnexpectedEndOfGame)
-            } else if self.turns[i].turns.len() == 0 {
-                Err(VisitingError::EmptyTurn)
-            } else {
-                self.j = 0;
-                Ok(self)
-            };
-        }
-        let len = self.turns[i].turns.len();
-        if j >= len {
-            Err(VisitingError::Internal)
-        } else if j + 1 == len {
-            if i + 1 == self.turns.len() {
-                Err(VisitingError::UnexpectedEndOfGame)
-            } else if self.turns[i + 1].turns.len() == 0 {
-                Err(VisitingError::EmptyTurn)
-            } else {
-                self.j = 0;
-                self.i += 1;
-                Ok(self)
-            }
-        } else {
-            self.j += 1;
-            Ok(self)
-        }
+        self.cursor.next(self.root)?;
+        Ok(self)
     }
-    fn into_alternative(self, alt: usize) -> Result<Self, VisitingError> {
-        if self.j == usize::MAX {
-            return Err(VisitingError::InvalidAlternative);
-        }
-        if alt == 0 || self.turns[self.i].turns[self.j].alternatives.len() < alt {
-            return Err(VisitingError::InvalidAlternative);
-        }
-        Ok(Self {
-            turns: &self.turns[self.i].turns[self.j].alternatives[alt - 1],
-            i: 0,
-            j: usize::MAX,
-        })
+    fn into_alternative(mut self, alt: usize) -> Result<Self, VisitingError> {
+        self.cursor.enter_alternative(self.root, alt)?;
+        Ok(self)
+    }
+    fn prev(mut self) -> Result<Self, VisitingError> {
+        self.cursor.prev(self.root)?;
+        Ok(self)
+    }
+    fn parent(mut self) -> Result<Self, VisitingError> {
+        self.cursor.parent(self.root)?;
+        Ok(self)
+    }
+    fn first(mut self) -> Self {
+        self.cursor.first();
+        self
+    }
+    fn end(mut self) -> Result<Self, VisitingError> {
+        self.cursor.end(self.root)?;
+        Ok(self)
     }
 }
 
 impl<'a> VisitorCommon for VisitorMut<'a> {
     fn last(&self) -> bool {
-        (self.i == 0 && self.j == usize::MAX && self.turns.len() == 0)
-            || (self.i == self.turns.len() - 1 && self.j == self.turns[self.i].turns.len() - 1)
+        self.cursor.last(self.root)
     }
     fn alternatives(&self) -> usize {
-        if self.j == usize::MAX {
-            0
-        } else {
-            self.turns[self.i].turns[self.j].alternatives.len()
-        }
+        self.cursor.alternatives(self.root)
     }
     fn qturn(&self) -> Option<&QuarterTurn> {
-        if self.j == usize::MAX {
-            None
-        } else {
-            Some(&self.turns[self.i].turns[self.j])
-        }
+        self.cursor.qturn(self.root)
+    }
+    fn path(&self) -> Path {
+        self.cursor.path(self.root)
     }
 
     fn next(mut self) -> Result<Self, VisitingError> {
-        let i = self.i;
-        let j = self.j;
-        if i == 0 && j == usize::MAX {
-            // special case for the beginning of line
-            return if self.turns.len() == 0 {
-                Err(VisitingError::UnexpectedEndOfGame)
-            } else if self.turns[i].turns.len() == 0 {
-                Err(VisitingError::EmptyTurn)
-            } else {
-                self.j = 0;
-                Ok(self)
-            };
-        }
-        let len = self.turns[i].turns.len();
-        if j >= len {
-            Err(VisitingError::Internal)
-        } else if j + 1 == len {
-            if i + 1 == self.turns.len() {
-                Err(VisitingError::UnexpectedEndOfGame)
-            } else if self.turns[i + 1].turns.len() == 0 {
-                Err(VisitingError::EmptyTurn)
-            } else {
-                self.j = 0;
-                self.i += 1;
-                Ok(self)
-            }
-        } else {
-            self.j += 1;
-            Ok(self)
-        }
+        self.cursor.next(self.root)?;
+        Ok(self)
     }
-    fn into_alternative(self, alt: usize) -> Result<Self, VisitingError> {
-        if self.j == usize::MAX {
-            return Err(VisitingError::InvalidAlternative);
-        }
-        if alt == 0 || self.turns[self.i].turns[self.j].alternatives.len() < alt {
-            return Err(VisitingError::InvalidAlternative);
-        }
-        Ok(Self {
-            turns: &mut self.turns[self.i].turns[self.j].alternatives[alt - 1],
-            i: 0,
-            j: usize::MAX,
-        })
+    fn into_alternative(mut self, alt: usize) -> Result<Self, VisitingError> {
+        self.cursor.enter_alternative(self.root, alt)?;
+        Ok(self)
+    }
+    fn prev(mut self) -> Result<Self, VisitingError> {
+        self.cursor.prev(self.root)?;
+        Ok(self)
+    }
+    fn parent(mut self) -> Result<Self, VisitingError> {
+        self.cursor.parent(self.root)?;
+        Ok(self)
+    }
+    fn first(mut self) -> Self {
+        self.cursor.first();
+        self
+    }
+    fn end(mut self) -> Result<Self, VisitingError> {
+        self.cursor.end(self.root)?;
+        Ok(self)
     }
 }
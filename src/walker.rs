@@ -0,0 +1,90 @@
+use crate::path::Path;
+use crate::types::*;
+
+/// Hooks called by [`PGN4::walk`] while going through a game in the order it is written.
+///
+/// Every hook does nothing by default, so a walker only needs the ones it cares about. The
+/// first error returned by a hook stops the walk.
+///
+/// For each quarterturn [`on_move`](Self::on_move) is called first, then
+/// [`on_comment`](Self::on_comment) if it has a description, then each of its alternatives
+/// is walked between [`begin_variation`](Self::begin_variation) and
+/// [`end_variation`](Self::end_variation).
+pub trait GameWalker {
+    type Error;
+    /// Called for each bracketed tag before any moves
+    fn on_tag(&mut self, _key: &str, _value: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    /// Called at the start of each turn with the path of its first move. The turn's
+    /// `number` and `double_dot` say how it is numbered.
+    fn begin_turn(&mut self, _path: &Path, _turn: &Turn) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    /// Called for each quarterturn with the path of the position after it
+    fn on_move(&mut self, _path: &Path, _qturn: &QuarterTurn) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    /// Called with the description of a quarterturn, right after [`on_move`](Self::on_move)
+    fn on_comment(&mut self, _path: &Path, _comment: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    /// Called before the moves of an alternative with the path of its first move
+    fn begin_variation(&mut self, _path: &Path) -> Result<(), Self::Error> {
+        Ok(())
+    }
+    /// Called after the moves of an alternative with the same path as
+    /// [`begin_variation`](Self::begin_variation)
+    fn end_variation(&mut self, _path: &Path) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl PGN4 {
+    /// Calls the hooks of `walker` for the tags and then every move of the game.
+    ///
+    /// Empty alternatives and turns are skipped.
+    pub fn walk<W: GameWalker>(&self, walker: &mut W) -> Result<(), W::Error> {
+        for (key, value) in &self.bracketed {
+            walker.on_tag(key, value)?;
+        }
+        walk_line(&self.turns, &[], walker)
+    }
+}
+
+fn walk_line<W: GameWalker>(
+    turns: &[Turn],
+    base: &[usize],
+    walker: &mut W,
+) -> Result<(), W::Error> {
+    let mut ply = 0;
+    for turn in turns.iter().filter(|t| !t.turns.is_empty()) {
+        for (i, qturn) in turn.turns.iter().enumerate() {
+            ply += 1;
+            let mut path = base.to_vec();
+            path.push(ply);
+            let path = Path(path);
+            if i == 0 {
+                walker.begin_turn(&path, turn)?;
+            }
+            walker.on_move(&path, qturn)?;
+            if let Some(description) = &qturn.description {
+                walker.on_comment(&path, description)?;
+            }
+            for (a, alt) in qturn.alternatives.iter().enumerate() {
+                if alt.is_empty() {
+                    continue;
+                }
+                let mut alt_base = path.to_vec();
+                alt_base.push(a + 1);
+                let mut first = alt_base.clone();
+                first.push(1);
+                let first = Path(first);
+                walker.begin_variation(&first)?;
+                walk_line(alt, &alt_base, walker)?;
+                walker.end_variation(&first)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,292 @@
+use crate::letters::PieceLetters;
+use crate::replay::Replay;
+use crate::types::*;
+
+use std::{fmt, io};
+
+/// Choices for how a [`PGN4`] is written
+///
+/// The default is [`WriteOptions::chess_com`], which is also what `Display` writes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WriteOptions {
+    /// Number of empty lines between the tags and the moves
+    pub blank_lines_after_tags: usize,
+    /// Lines of moves longer than this many characters are broken at a space.
+    /// Descriptions are never broken up.
+    pub line_width: Option<usize>,
+    /// Put every variation on its own lines, indented by this many spaces per level.
+    /// Without it, variations are written inline like Chess.com does.
+    pub indent_variations: Option<usize>,
+    /// Write the descriptions of moves
+    pub descriptions: bool,
+    /// Write variations
+    pub variations: bool,
+    /// Letters to write pieces with
+    pub letters: PieceLetters,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self::chess_com()
+    }
+}
+
+impl WriteOptions {
+    /// Exactly the layout Chess.com exports and imports
+    pub fn chess_com() -> Self {
+        Self {
+            blank_lines_after_tags: 3,
+            line_width: None,
+            indent_variations: None,
+            descriptions: true,
+            variations: true,
+            letters: PieceLetters::english(),
+        }
+    }
+    /// Easier to read for people: 80 character lines and indented variations
+    pub fn readable() -> Self {
+        Self {
+            blank_lines_after_tags: 1,
+            line_width: Some(80),
+            indent_variations: Some(2),
+            ..Self::chess_com()
+        }
+    }
+    /// Writes `pgn` to `out` as it goes
+    pub fn write<W: io::Write>(&self, pgn: &PGN4, out: W) -> io::Result<()> {
+        let mut adapter = IoAdapter {
+            inner: out,
+            error: Ok(()),
+        };
+        let written = {
+            let mut writer = Writer::new(&mut adapter, self);
+            writer.pgn(pgn, None).and_then(|_| writer.finish())
+        };
+        match (written, adapter.error) {
+            (_, Err(e)) => Err(e),
+            (Err(_), Ok(())) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "failed to format the game",
+            )),
+            (Ok(()), Ok(())) => Ok(()),
+        }
+    }
+    /// Writes `pgn` to a string
+    pub fn format(&self, pgn: &PGN4) -> String {
+        let mut out = String::new();
+        let mut writer = Writer::new(&mut out, self);
+        writer
+            .pgn(pgn, None)
+            .and_then(|_| writer.finish())
+            .expect("Writing to a String doesn't fail");
+        out
+    }
+}
+
+/// Lets the writer, which works with `fmt::Write`, stream to an `io::Write`
+struct IoAdapter<W: io::Write> {
+    inner: W,
+    error: io::Result<()>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Err(e);
+            fmt::Error
+        })
+    }
+}
+
+/// Lays out a game following some [`WriteOptions`]
+///
+/// Moves are written in short notation if the position they are played from is passed in
+/// and in long notation otherwise.
+pub(crate) struct Writer<'a, W: fmt::Write> {
+    out: W,
+    options: &'a WriteOptions,
+    /// The current line, kept back until it is complete if lines are wrapped
+    line: String,
+    /// How many variations deep the writer is
+    depth: usize,
+    /// A variation just closed, so the line should be broken before anything else
+    pending_break: bool,
+}
+
+impl<'a, W: fmt::Write> Writer<'a, W> {
+    pub(crate) fn new(out: W, options: &'a WriteOptions) -> Self {
+        Self {
+            out,
+            options,
+            line: String::new(),
+            depth: 0,
+            pending_break: false,
+        }
+    }
+
+    /// Writes out the last line
+    pub(crate) fn finish(&mut self) -> fmt::Result {
+        let line = std::mem::take(&mut self.line);
+        self.out.write_str(&line)
+    }
+
+    fn text(&mut self, text: &str) -> fmt::Result {
+        let width = match self.options.line_width {
+            Some(width) => width,
+            None => return self.out.write_str(text),
+        };
+        for (i, part) in text.split('\n').enumerate() {
+            if i != 0 {
+                let line = std::mem::take(&mut self.line);
+                self.out.write_str(&line)?;
+                self.out.write_char('\n')?;
+            }
+            self.line.push_str(part);
+            self.wrap(width)?;
+        }
+        Ok(())
+    }
+
+    /// Breaks the current line at spaces until it fits in `width`, if possible
+    fn wrap(&mut self, width: usize) -> fmt::Result {
+        let indent = self.indent();
+        while self.line.chars().count() > width {
+            // Spaces that are not inside a description or the indentation
+            let mut in_description = false;
+            let mut spaces = Vec::new();
+            for (count, (i, c)) in self.line.char_indices().enumerate() {
+                match c {
+                    '{' => in_description = true,
+                    '}' => in_description = false,
+                    ' ' if !in_description && !self.line[..i].trim().is_empty() => {
+                        spaces.push((count, i))
+                    }
+                    _ => {}
+                }
+            }
+            let fitting = spaces.iter().rev().find(|(count, _)| *count <= width);
+            let split = match fitting.or_else(|| spaces.first()) {
+                Some((_, split)) => *split,
+                None => return Ok(()),
+            };
+            let rest = format!("{}{}", indent, self.line[split..].trim_start());
+            self.out.write_str(self.line[..split].trim_end())?;
+            self.out.write_char('\n')?;
+            self.line = rest;
+        }
+        Ok(())
+    }
+
+    fn indent(&self) -> String {
+        " ".repeat(self.options.indent_variations.unwrap_or(0) * self.depth)
+    }
+
+    /// Starts a new line at the current indentation
+    fn break_line(&mut self) -> fmt::Result {
+        self.pending_break = false;
+        let indent = self.indent();
+        self.text("\n")?;
+        self.text(&indent)
+    }
+
+    pub(crate) fn pgn(&mut self, pgn: &PGN4, mut replay: Option<&mut Replay>) -> fmt::Result {
+        if !pgn.bracketed.is_empty() {
+            // Tags are never wrapped
+            for bracket in &pgn.bracketed {
+                writeln!(self.out, "[{} \"{}\"]", bracket.0, bracket.1)?;
+            }
+            for _ in 0..self.options.blank_lines_after_tags {
+                self.out.write_char('\n')?;
+            }
+        }
+        if !pgn.turns.is_empty() {
+            self.turn(&pgn.turns[0], replay.as_deref_mut())?;
+            for turn in &pgn.turns[1..] {
+                self.break_line()?;
+                self.turn(turn, replay.as_deref_mut())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn turn(&mut self, turn: &Turn, mut replay: Option<&mut Replay>) -> fmt::Result {
+        if turn.number != 0 {
+            self.text(&turn.number.to_string())?;
+        }
+        if turn.double_dot {
+            self.text(".. ")?;
+        } else {
+            self.text(". ")?;
+        }
+        self.quarter(&turn.turns[0], replay.as_deref_mut())?;
+        for quarter in &turn.turns[1..] {
+            if self.pending_break {
+                self.break_line()?;
+                self.text(".. ")?;
+            } else {
+                self.text(" .. ")?;
+            }
+            self.quarter(quarter, replay.as_deref_mut())?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn quarter(
+        &mut self,
+        quarter: &QuarterTurn,
+        replay: Option<&mut Replay>,
+    ) -> fmt::Result {
+        let main = match &replay {
+            Some(r) => r.to_short(&quarter.main),
+            None => quarter.main.to_string(),
+        };
+        let main = self.options.letters.localize_move(&main);
+        self.text(&quarter.move_text_with(&main))?;
+        if let Some(d) = quarter
+            .description
+            .as_ref()
+            .filter(|_| self.options.descriptions)
+        {
+            self.text(&format!(" {{ {} }}", d))?;
+        }
+        let alternatives = if self.options.variations {
+            &quarter.alternatives[..]
+        } else {
+            &[]
+        };
+        // Just ignore empty alternatives if they are for some reason instantiated.
+        for alt in alternatives.iter().filter(|alt| !alt.is_empty()) {
+            // Alternatives are played from the position before this quarter turn
+            let mut branch = replay.as_deref().cloned();
+            if self.options.indent_variations.is_some() {
+                self.depth += 1;
+                self.break_line()?;
+                self.text(if alt[0].number == 0 { "( " } else { "(" })?;
+            } else if alt[0].number == 0 {
+                // The initial '(' has different whitespace if the alternative starts
+                // in the middle of a turn version the beginning
+                self.text(" ( ")?;
+            } else {
+                self.text("\n(")?;
+            }
+            // Write \n separated turns
+            self.turn(&alt[0], branch.as_mut())?;
+            for turn in &alt[1..] {
+                self.break_line()?;
+                self.turn(turn, branch.as_mut())?;
+            }
+            if self.options.indent_variations.is_some() {
+                self.text(" )")?;
+                self.depth -= 1;
+                self.pending_break = true;
+            } else {
+                // The ending always has space on both sides
+                self.text(" ) ")?;
+            }
+        }
+        if let Some(r) = replay {
+            r.play(quarter).map_err(|_| fmt::Error)?;
+        }
+        Ok(())
+    }
+}
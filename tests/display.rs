@@ -74,3 +74,19 @@ fn full_turn_diff() {
     let string = turn.to_string();
     assert_eq!(expected, string, "Full turn diff doesn't display correctly");
 }
+
+#[test]
+fn move_text() {
+    let mut qturn = QuarterTurn {
+        main: Move::Normal("Kh1-i2".parse().unwrap()),
+        modifier: Some(Move::Resign),
+        extra_stalemate: false,
+        description: Some("Not included".to_owned()),
+        alternatives: Vec::new(),
+    };
+    assert_eq!(qturn.move_text(), "Kh1-i2R");
+    qturn.modifier = None;
+    qturn.extra_stalemate = true;
+    assert_eq!(qturn.move_text(), "Kh1-i2S");
+    assert_eq!(qturn.to_string(), "Kh1-i2S { Not included }");
+}
@@ -0,0 +1,86 @@
+use pgn4::*;
+
+const NESTED: &str = "[Variant \"FFA\"]
+
+
+
+1. h2-h3 .. b7-c7 ( .. b7-d7 .. g13-g11 ( .. h13-h11 ( .. e13-e12 )  )  .. m8-l8 )  .. g13-g12 .. m8-l8
+2. e2-e3 .. c7-d7";
+
+fn parse(s: &str) -> PGN4 {
+    s.parse().unwrap()
+}
+
+#[test]
+fn hoist() {
+    let mut pgn = parse(NESTED);
+    let changes = pgn.flatten_variations(FlattenPolicy::Hoist);
+    assert_eq!(
+        changes,
+        vec![
+            Flattened::Hoisted {
+                from: "2-1-2-1-1".parse().unwrap(),
+                to: "2-2-2".parse().unwrap()
+            },
+            Flattened::Hoisted {
+                from: "2-1-2-1-1-1-1".parse().unwrap(),
+                to: "2-3-2".parse().unwrap()
+            },
+        ]
+    );
+    let expected = "[Variant \"FFA\"]
+
+
+
+1. h2-h3 .. b7-c7 ( .. b7-d7 .. g13-g11 .. m8-l8 )  ( .. b7-d7 .. h13-h11 )  ( .. b7-d7 .. e13-e12 )  .. g13-g12 .. m8-l8
+2. e2-e3 .. c7-d7";
+    assert_eq!(pgn.to_string(), expected);
+    assert_eq!(pgn, parse(expected));
+    // The hoisted lines reach the same positions
+    let original = parse(NESTED);
+    assert_eq!(
+        original
+            .fen4_at(&[2, 1, 2, 1, 1, 1, 1])
+            .unwrap()
+            .to_string(),
+        pgn.fen4_at(&[2, 3, 2]).unwrap().to_string()
+    );
+    assert_eq!(
+        original.fen4_at(&[2, 1, 2, 1, 1]).unwrap().to_string(),
+        pgn.fen4_at(&[2, 2, 2]).unwrap().to_string()
+    );
+
+    // Nothing is left to change
+    assert_eq!(pgn.flatten_variations(FlattenPolicy::Hoist), vec![]);
+    assert_eq!(pgn.to_string(), expected);
+}
+
+#[test]
+fn hoist_across_turns() {
+    let mut pgn = parse(
+        "1. h2-h3\n(1.. j2-j3 .. b7-c7 .. g13-g12 .. m8-l8\n2. Nj1-i3\n(2.. e2-e3 )  .. c7-d7 )  .. b7-c7",
+    );
+    pgn.flatten_variations(FlattenPolicy::Hoist);
+    let expected = "1. h2-h3\n(1.. j2-j3 .. b7-c7 .. g13-g12 .. m8-l8\n2. Nj1-i3 .. c7-d7 ) \n(1.. j2-j3 .. b7-c7 .. g13-g12 .. m8-l8\n2. e2-e3 )  .. b7-c7";
+    assert_eq!(pgn.to_string(), expected);
+    assert_eq!(pgn, parse(expected));
+}
+
+#[test]
+fn drop() {
+    let mut pgn = parse(NESTED);
+    let changes = pgn.flatten_variations(FlattenPolicy::Drop);
+    assert_eq!(
+        changes,
+        vec![Flattened::Dropped("2-1-2-1-1".parse().unwrap())]
+    );
+    assert_eq!(
+        pgn.to_string(),
+        "[Variant \"FFA\"]
+
+
+
+1. h2-h3 .. b7-c7 ( .. b7-d7 .. g13-g11 .. m8-l8 )  .. g13-g12 .. m8-l8
+2. e2-e3 .. c7-d7"
+    );
+}
@@ -0,0 +1,99 @@
+use pgn4::*;
+
+const GAMES: [&str; 3] = [
+    "[Variant \"FFA\"]
+[Site \"4 Player Chess\"]
+1. h2-h3 { opening } .. b7-c7
+( .. b8-c8 { another } .. g13-g12
+( .. k13-k12 )  .. Nn10-l9
+2. e2-e3 )  .. g13-g12 .. Nn10-l9
+2. e2-e3
+(2.. d2-d3 .. b11-c11 )  .. b10-c10",
+    "1. d2-d3
+(1.. j2-j3 .. b5-c5 .. e13-e12 .. m10-l10
+2. e2-e3 .. b10-c10 .. j13-j12 .. m5-l5 )  .. b11-c11 .. k13-k12 .. m4-l4
+2. h2-h3 .. b7-c7 .. g13-g12 .. m8-l8",
+    "1. d2-d3
+(1.. j2-j3 )  .. b11-c11 ( .. b5-c5 )  .. k13-k12 ( .. e13-e12 )  .. m4-l4 ( .. m10-l10 )
+2. h2-h3
+(2.. e2-e3 )  .. b7-c7 ( .. b10-c10 )  .. g13-g12 ( .. j13-j12 )  .. m8-l8 ( .. m5-l5 )",
+];
+
+#[test]
+fn round_trip() {
+    for game in &GAMES {
+        let pgn: PGN4 = game.parse().unwrap();
+        let tree = GameTree::from_pgn4(&pgn);
+        assert_eq!(tree.to_pgn4(), pgn);
+        assert_eq!(PGN4::from(&tree).to_string(), pgn.to_string());
+    }
+    // A mainline starting in the middle of a turn keeps its "1.."
+    let mid = GAMES[0]
+        .parse::<PGN4>()
+        .unwrap()
+        .starting_from(&[1])
+        .unwrap();
+    assert!(mid.turns[0].double_dot);
+    assert_eq!(GameTree::from_pgn4(&mid).to_pgn4(), mid);
+    let empty = GameTree::from(&PGN4 {
+        bracketed: vec![],
+        turns: vec![],
+    });
+    assert!(empty.roots().is_empty());
+    assert!(empty.to_pgn4().turns.is_empty());
+}
+
+#[test]
+fn numbers_are_recomputed() {
+    let pgn: PGN4 = GAMES[0].parse().unwrap();
+    let mut renumbered = pgn.clone();
+    for (i, turn) in renumbered.turns.iter_mut().enumerate() {
+        turn.number = 42;
+        // The first turn's double dot says if the game starts in the middle of a turn
+        turn.double_dot = i > 0;
+    }
+    assert_eq!(GameTree::from_pgn4(&renumbered).to_pgn4(), pgn);
+}
+
+#[test]
+fn navigation() {
+    let pgn: PGN4 = GAMES[0].parse().unwrap();
+    let tree = GameTree::from_pgn4(&pgn);
+    let text = |id: NodeId| tree.node(id).main.to_string();
+
+    let first = tree.roots()[0];
+    assert_eq!(tree.parent(first), None);
+    let second = tree.main_child(first).unwrap();
+    // b7-c7 and its alternative b8-c8 are both played after h2-h3
+    let after_first: Vec<String> = tree.children(first).iter().map(|c| text(*c)).collect();
+    assert_eq!(after_first, vec!["b7-c7", "b8-c8"]);
+    let alternative = tree.children(first)[1];
+    assert_eq!(tree.variation_of(alternative), Some(second));
+    assert_eq!(tree.parent(alternative), Some(first));
+
+    // The nested alternative hangs off g13-g12 of the variation, but is played after b8-c8
+    let nested = tree.node_at(&[2, 1, 2, 1, 1]).unwrap();
+    assert_eq!(text(nested), "k13-k12");
+    assert_eq!(tree.parent(nested), Some(alternative));
+    assert_eq!(tree.siblings(nested).len(), 2);
+    assert_eq!(tree.node_at(&[2, 1, 4]).map(text), Some("e2-e3".to_owned()));
+    assert_eq!(tree.node_at(&[2, 1, 5]), None);
+    assert_eq!(tree.node_at(&[2, 2, 1]), None);
+    assert_eq!(tree.node_at(&[0]), None);
+
+    // Every node's path leads back to it and to the same move in the PGN4
+    let mut ids = tree.roots().to_vec();
+    let mut count = 0;
+    while let Some(id) = ids.pop() {
+        count += 1;
+        ids.extend(tree.children(id));
+        let path = tree.path(id);
+        assert_eq!(tree.node_at(&path), Some(id));
+        let (_, last) = pgn.position_at(&path).unwrap();
+        if let Move::Normal(m) = &tree.node(id).main {
+            assert_eq!(last, vec![m.from, m.to]);
+        }
+    }
+    assert_eq!(count, tree.nodes().len());
+    assert_eq!(tree.path(nested).to_string(), "2-1-2-1-1");
+}
@@ -0,0 +1,71 @@
+use pgn4::*;
+
+const VARIATIONS: &str = "[Variant \"FFA\"]
+[Red \"A & B\"]
+
+
+
+1. h2-h3 .. b7-c7 .. g13-g12 ( .. h13-h12 .. m8-l8
+2. R\n(2.. e2-e3 )  )  .. Nn10-l9 { Develops <the> knight }
+2. j2-j3\n(2.. Nj1-i3 .. Na5-c6 )  .. b11-c11";
+
+#[test]
+fn page() {
+    let pgn: PGN4 = VARIATIONS.parse().unwrap();
+    let html = pgn
+        .to_html(&HtmlOptions {
+            title: Some("Game <1>".to_owned()),
+            ..HtmlOptions::default()
+        })
+        .unwrap();
+    assert!(html.starts_with(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Game &lt;1&gt;</title>"
+    ));
+    assert!(html.ends_with("</div>\n</body>\n</html>\n"));
+    assert!(html.contains("<tr><th>Red</th><td>A &amp; B</td></tr>"));
+    assert!(html.contains("<script>"));
+    // Nothing is loaded from elsewhere
+    assert!(!html.contains("src="));
+    assert!(!html.contains("href="));
+    assert!(!html.contains("http:"));
+    assert!(!html.contains("https:"));
+}
+
+#[test]
+fn moves() {
+    let pgn: PGN4 = VARIATIONS.parse().unwrap();
+    let html = pgn
+        .to_html(&HtmlOptions {
+            standalone: false,
+            script: false,
+            ..HtmlOptions::default()
+        })
+        .unwrap();
+    assert!(html.starts_with("<div class=\"pgn4\">\n<style>"));
+    assert!(!html.contains("<script>"));
+    assert!(html.contains(
+        "<div class=\"turn\"><span class=\"number red\">1.</span> <span class=\"move red\" data-path=\"1\">h2-h3</span> .. <span class=\"move blue\" data-path=\"2\">b7-c7</span> .. <span class=\"move yellow\" data-path=\"3\">g13-g12</span>\n<details class=\"variation\" open><summary>.. h13-h12</summary>\n<div class=\"turn\"><span class=\"number yellow\">..</span> <span class=\"move yellow\" data-path=\"3,1,1\">h13-h12</span> .. <span class=\"move green\" data-path=\"3,1,2\">m8-l8</span></div>\n<div class=\"turn\"><span class=\"number red\">2.</span> <span class=\"move red\" data-path=\"3,1,3\">R</span>\n<details class=\"variation\" open><summary>2.. e2-e3</summary>\n<div class=\"turn\"><span class=\"number red\">2..</span> <span class=\"move red\" data-path=\"3,1,3,1,1\">e2-e3</span></div>\n</details>\n</div>\n</details>\n .. <span class=\"move green\" data-path=\"4\">Nn10-l9</span> <span class=\"comment\">Develops &lt;the&gt; knight</span></div>\n"
+    ));
+    assert!(html.contains(".pgn4 .yellow { color: #c09526; }"));
+    assert_eq!(html.matches("<details").count(), 3);
+    assert_eq!(html.matches("data-path=").count(), 12);
+}
+
+#[test]
+fn unplayable() {
+    let pgn: PGN4 = "[Variant \"FFA\"]\n\n\n\n1. h2-h5".parse().unwrap();
+    assert!(pgn.to_html(&HtmlOptions::default()).is_err());
+}
+
+#[test]
+fn modifier_in_summary() {
+    let pgn: PGN4 = "[Variant \"FFA\"]
+[RuleVariants \"DeadKingWalking\"]
+
+1. R .. b7-c7 .. e13-e12 .. m8-l8
+2. Kh1xh2R\n(2.. Kh1xi2R )  .. c7-d7"
+        .parse()
+        .unwrap();
+    let html = pgn.to_html(&HtmlOptions::default()).unwrap();
+    assert!(html.contains("<summary>2.. Kh1xi2R</summary>"));
+}
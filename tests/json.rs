@@ -0,0 +1,82 @@
+#![cfg(feature = "json")]
+use pgn4::*;
+
+const VARIATIONS: &str = "[Variant \"FFA\"]
+
+
+
+1. h2-h3 { date=2020-07-17T17:13:12.297Z clock=178312 } .. b7-c7 .. g13-g12 ( .. h13-h12 .. m8-l8
+2. R\n(2.. e2-e3 )  )  .. Nn10-l9 { Develops the knight }
+2. j2-j3\n(2.. Nj1-i3 .. Na5-c6 )  .. b11-c11";
+
+#[test]
+fn round_trip() {
+    let pgn: PGN4 = VARIATIONS.parse().unwrap();
+    let json = pgn.to_json().unwrap();
+    let back = PGN4::from_json(&json).unwrap();
+    assert_eq!(back, pgn);
+    assert_eq!(back.to_string(), VARIATIONS);
+
+    // The mainline keeps its "1.." when it starts in the middle of a turn
+    let mid = pgn.starting_from(&[2]).unwrap();
+    let game = mid.to_json_game().unwrap();
+    assert!(game.mid_turn);
+    assert_eq!(PGN4::from_json(&mid.to_json().unwrap()).unwrap(), mid);
+}
+
+#[test]
+fn nodes() {
+    let pgn: PGN4 = VARIATIONS.parse().unwrap();
+    let game = pgn.to_json_game().unwrap();
+    assert_eq!(game.tags, vec![("Variant".to_owned(), "FFA".to_owned())]);
+    assert_eq!(game.moves.len(), 6);
+
+    let first = &game.moves[0];
+    assert_eq!(first.path, vec![1]);
+    assert_eq!((first.ply, first.turn), (1, 1));
+    assert_eq!(first.color, "R");
+    assert_eq!(first.text, "h2-h3");
+    assert_eq!(first.piece, Some('P'));
+    assert_eq!(first.from.as_deref(), Some("h2"));
+    assert_eq!(first.to.as_deref(), Some("h3"));
+    assert_eq!(first.clock, Some(178312));
+
+    // The variation replaces Yellow's first move
+    let yellow = &game.moves[2];
+    assert_eq!(yellow.color, "Y");
+    let variation = &yellow.variations[0];
+    assert_eq!(variation[0].path, vec![3, 1, 1]);
+    assert_eq!((variation[0].ply, variation[0].turn), (3, 1));
+    assert_eq!(variation[0].color, "Y");
+    let resign = &variation[2];
+    assert_eq!((resign.ply, resign.turn), (5, 2));
+    assert_eq!(resign.color, "R");
+    assert_eq!(resign.text, "R");
+    assert_eq!(resign.piece, None);
+    assert_eq!(resign.variations[0][0].path, vec![3, 1, 3, 1, 1]);
+
+    let knight = &game.moves[3];
+    assert_eq!(knight.color, "G");
+    assert_eq!(knight.comment.as_deref(), Some("Develops the knight"));
+    assert_eq!(knight.clock, None);
+
+    let second = &game.moves[4];
+    assert_eq!((second.ply, second.turn), (5, 2));
+    assert_eq!(second.variations[0][1].color, "B");
+    assert_eq!(second.variations[0][1].turn, 2);
+}
+
+#[test]
+fn bad_input() {
+    assert!(matches!(PGN4::from_json("{"), Err(JsonError::Json(_))));
+    let pgn: PGN4 = VARIATIONS.parse().unwrap();
+    let mut game = pgn.to_json_game().unwrap();
+    game.moves[1].text = "b7-c".to_owned();
+    assert!(matches!(game.to_pgn4(), Err(JsonError::BadMove(_, _, path)) if path == vec![2]));
+    game.moves[1].text = "b7-c7".to_owned();
+    game.moves[1].variations.push(Vec::new());
+    assert!(matches!(
+        game.to_pgn4(),
+        Err(JsonError::EmptyVariation(path)) if path == vec![2, 1]
+    ));
+}
@@ -0,0 +1,73 @@
+use pgn4::*;
+
+const OPENING: &str = "[Variant \"Teams\"]
+
+
+
+1. h2-h3 .. b8-c8 .. g13-g12 .. m7-l7
+2. Qg1xQn8+ .. Qa7xQh14+ .. Kg14xQh14 .. Kn7xQn8
+3. Bi1xBa9 .. Ka8xBa9 .. Bf14xBn6 .. m4-l4
+4. e2-e3 .. Na10-c9 .. Bn6-j10 .. Nn5-l6
+5. Ne1-f3 .. Na5-c6 .. j13-j12 .. Kn8-n7
+6. Nf3-e5 .. Nc6xNe5 .. Bj10xNe5+ .. Nn10-l9
+7. T .. R";
+
+const GERMAN: &str = "[Variant \"Teams\"]
+
+
+
+1. h2-h3 .. b8-c8 .. g13-g12 .. m7-l7
+2. Dg1xDn8+ .. Da7xDh14+ .. Kg14xDh14 .. Kn7xDn8
+3. Li1xLa9 .. Ka8xLa9 .. Lf14xLn6 .. m4-l4
+4. e2-e3 .. Sa10-c9 .. Ln6-j10 .. Sn5-l6
+5. Se1-f3 .. Sa5-c6 .. j13-j12 .. Kn8-n7
+6. Sf3-e5 .. Sc6xSe5 .. Lj10xSe5+ .. Sn10-l9
+7. T .. R";
+
+fn german() -> PieceLetters {
+    PieceLetters::new(&[('Q', 'D'), ('R', 'T'), ('B', 'L'), ('N', 'S')])
+}
+
+#[test]
+fn english_is_default() {
+    let pgn: PGN4 = OPENING.parse().unwrap();
+    let english = PieceLetters::default();
+    assert_eq!(pgn.with_letters(&english).to_string(), pgn.to_string());
+    assert_eq!(pgn.to_string(), OPENING);
+}
+
+#[test]
+fn german_round_trip() {
+    let pgn: PGN4 = OPENING.parse().unwrap();
+    assert_eq!(pgn.with_letters(&german()).to_string(), GERMAN);
+    assert_eq!(PGN4::parse_with_letters(GERMAN, &german()), Ok(pgn));
+}
+
+#[test]
+fn figurines() {
+    let figurines = PieceLetters::figurines();
+    let pgn: PGN4 = "1. Qg1xQn8+ .. Ka8xBa9 .. g13-g12 .. h8-g8=D\n2. O-O .. Kh1xh2R"
+        .parse()
+        .unwrap();
+    let written = pgn.with_letters(&figurines).to_string();
+    assert_eq!(
+        written,
+        "1. ♕g1x♕n8+ .. ♔a8x♗a9 .. g13-g12 .. h8-g8=♛\n2. O-O .. ♔h1xh2R"
+    );
+    assert_eq!(PGN4::parse_with_letters(&written, &figurines), Ok(pgn));
+
+    let m: Move = "Rd4xBg4".parse().unwrap();
+    assert_eq!(figurines.write_move(&m), "♖d4x♗g4");
+    assert_eq!(figurines.english_move("♖d4x♗g4").parse::<Move>(), Ok(m));
+}
+
+#[test]
+fn short_and_localized() {
+    let pgn: PGN4 = OPENING.parse().unwrap();
+    let short = pgn
+        .short_notation()
+        .unwrap()
+        .with_letters(&german())
+        .to_string();
+    assert!(short.contains("2. Dxn8+ .. Dxh14+ .. Kxh14 .. Kxn8\n3. Lxa9"));
+}
@@ -196,3 +196,117 @@ fn deletion() {
 }
 
 // TODO: test deletion of nested and multiple alternatives
+
+#[test]
+fn edit_errors() {
+    use pgn4::EditError;
+    let mut base: pgn4::PGN4 = ADDED_ON_FIRST.parse().unwrap();
+    let to_add: pgn4::PGN4 = TO_ADD.parse().unwrap();
+    let qturn = to_add.turns[0].turns[0].clone();
+    let unchanged = base.clone();
+
+    assert_eq!(
+        base.append_move(&[1, 1], qturn.clone()),
+        Err(EditError::EvenPath)
+    );
+    assert_eq!(
+        base.append_move(&[1, 0, 1], qturn.clone()),
+        Err(EditError::ZeroInPath)
+    );
+    assert_eq!(
+        base.append_move(&[9], qturn.clone()),
+        Err(EditError::PastEnd)
+    );
+    assert_eq!(
+        base.append_move(&[2, 1, 1], qturn),
+        Err(EditError::MissingAlternative)
+    );
+    assert_eq!(
+        base.promote_to_mainline(&[1, 2, 1]),
+        Err(EditError::MissingAlternative)
+    );
+    assert_eq!(base.promote_to_mainline(&[0]), Err(EditError::StartOfGame));
+    assert_eq!(base.delete_from(&[0]), Err(EditError::StartOfGame));
+    assert_eq!(base.delete_from(&[1, 1, 9]), Err(EditError::PastEnd));
+    assert_eq!(base, unchanged);
+}
+
+#[test]
+fn visitor_path() {
+    let mut base: pgn4::PGN4 = ADDED_ON_FIRST.parse().unwrap();
+    let mut visitor = pgn4::Visitor::new(&base);
+    assert_eq!(visitor.path().to_string(), "0");
+    visitor = visitor.next().unwrap();
+    assert_eq!(visitor.path().to_string(), "1");
+    visitor = visitor.into_alternative(1).unwrap();
+    // Before the first move of the alternative is before the move it replaces
+    assert_eq!(visitor.path().to_string(), "0");
+    visitor = visitor.next().unwrap().next().unwrap().next().unwrap();
+    assert_eq!(visitor.path().to_string(), "1-1-3");
+
+    let mut visitor = pgn4::VisitorMut::new(&mut base);
+    visitor = visitor.next().unwrap().into_alternative(1).unwrap();
+    visitor = visitor.next().unwrap().next().unwrap();
+    let path = visitor.path();
+    assert_eq!(path.as_slice(), &[1, 1, 2]);
+    assert_eq!(
+        visitor.reborrow().next().unwrap().path().to_string(),
+        "1-1-3"
+    );
+    base.delete_from(&path).unwrap();
+    let mut expected: pgn4::PGN4 = ADDED_ON_FIRST.parse().unwrap();
+    expected.turns[0].turns[0].alternatives[0].truncate(1);
+    expected.turns[0].turns[0].alternatives[0][0]
+        .turns
+        .truncate(1);
+    assert_eq!(base, expected);
+}
+
+#[test]
+fn visitor_navigation() {
+    use pgn4::VisitingError;
+    let mut base: pgn4::PGN4 = ADDED_ON_FIRST.parse().unwrap();
+    let visitor = pgn4::Visitor::new(&base);
+    assert_eq!(
+        visitor.clone().prev().unwrap_err(),
+        VisitingError::UnexpectedStartOfGame
+    );
+    let visitor = visitor.end().unwrap();
+    assert_eq!(visitor.path().to_string(), "8");
+    let visitor = visitor.prev().unwrap().prev().unwrap();
+    assert_eq!(visitor.path().to_string(), "6");
+    let visitor = visitor.first();
+    assert_eq!(visitor.path().to_string(), "0");
+    assert_eq!(
+        visitor.clone().parent().unwrap_err(),
+        VisitingError::InMainline
+    );
+
+    let visitor = visitor.next().unwrap().into_alternative(1).unwrap();
+    let visitor = visitor.end().unwrap();
+    assert_eq!(visitor.path().to_string(), "1-1-8");
+    assert_eq!(visitor.qturn().unwrap().main.to_string(), "m5-l5");
+    let visitor = visitor.parent().unwrap();
+    assert_eq!(visitor.path().to_string(), "1");
+    assert_eq!(visitor.qturn().unwrap().main.to_string(), "d2-d3");
+
+    // Going back from the first move of an alternative leaves it
+    let visitor = visitor.into_alternative(1).unwrap().next().unwrap();
+    assert_eq!(visitor.path().to_string(), "1-1-1");
+    let visitor = visitor.prev().unwrap();
+    assert_eq!(visitor.path().to_string(), "0");
+    assert!(visitor.qturn().is_none());
+
+    // VisitorMut can backtrack without reborrowing
+    let mut visitor = pgn4::VisitorMut::new(&mut base);
+    visitor = visitor.next().unwrap().into_alternative(1).unwrap();
+    visitor = visitor.next().unwrap().next().unwrap();
+    visitor = visitor.prev().unwrap();
+    visitor.qturn_mut().unwrap().description = Some("back".to_owned());
+    visitor = visitor.parent().unwrap().next().unwrap();
+    assert_eq!(visitor.qturn().unwrap().main.to_string(), "b11-c11");
+    assert_eq!(
+        base.turns[0].turns[0].alternatives[0][0].turns[0].description,
+        Some("back".to_owned())
+    );
+}
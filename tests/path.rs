@@ -0,0 +1,77 @@
+use pgn4::*;
+use std::convert::TryFrom;
+
+const GAME: &str = "[Variant \"FFA\"]
+
+
+
+1. h2-h3 .. b7-c7 .. g13-g12 .. Nn10-l9
+2. e2-e3 ( .. Qg1-j4 .. Qa7-b7 )  .. Qa7-b7";
+
+#[test]
+fn parse_and_format() {
+    let path: Path = "3-1-2".parse().unwrap();
+    assert_eq!(path.as_slice(), &[3, 1, 2]);
+    assert_eq!(path.to_string(), "3-1-2");
+    assert_eq!(path.depth(), 1);
+    assert_eq!(path.ply(), 2);
+    assert_eq!(Path::start().to_string(), "0");
+    assert!("0".parse::<Path>().unwrap().is_start());
+
+    assert_eq!(
+        "3-x".parse::<Path>(),
+        Err(PathError::NotANumber("x".to_owned()))
+    );
+    assert_eq!(
+        "3-1".parse::<Path>(),
+        Err(PathError::Invalid(VisitingError::EvenPath))
+    );
+    assert_eq!(Path::new(vec![3, 0, 1]), Err(VisitingError::ZeroInPath));
+    assert_eq!(
+        Path::try_from(&[0, 1, 1][..]),
+        Err(VisitingError::ZeroInPath)
+    );
+}
+
+#[test]
+fn push_and_pop() {
+    let mut path = Path::start();
+    assert_eq!(
+        path.push_variation(1),
+        Err(VisitingError::InvalidAlternative)
+    );
+    path.push_ply();
+    path.push_ply();
+    assert_eq!(path, Path::mainline(2));
+    path.push_variation(1).unwrap();
+    path.push_ply();
+    assert_eq!(path.to_string(), "2-1-2");
+
+    let mut up = path.clone();
+    assert_eq!(up.pop_variation(), Some(1));
+    assert_eq!(up, Path::mainline(2));
+    assert_eq!(up.pop_variation(), None);
+
+    assert!(path.pop_ply());
+    assert_eq!(path.to_string(), "2-1-1");
+    // The first move of a variation replaces move 2, so one back is move 1
+    assert!(path.pop_ply());
+    assert_eq!(path, Path::mainline(1));
+    assert!(path.pop_ply());
+    assert!(path.is_start());
+    assert!(!path.pop_ply());
+}
+
+#[test]
+fn accepted_by_apis() {
+    let mut pgn: PGN4 = GAME.parse().unwrap();
+    let path: Path = "5-1-2".parse().unwrap();
+    let board = pgn.fen4_at(&path).unwrap();
+    assert_eq!(board, pgn.fen4_at(&[5, 1, 2]).unwrap());
+    pgn.render_text_at(&path, &TextOptions::default()).unwrap();
+
+    pgn.promote_to_mainline(&path).unwrap();
+    assert_eq!(pgn.fen4_at(&Path::mainline(6)).unwrap(), board);
+    pgn.delete_from(&Path::mainline(6)).unwrap();
+    assert!(pgn.fen4_at(&Path::mainline(6)).is_err());
+}
@@ -0,0 +1,106 @@
+use fen4::Board;
+use pgn4::*;
+
+const ROOK_AND_KINGS: &str = "R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/bK,13/13,gK/14/14/3,rR,10/14/14/7,rK,6";
+
+const MATE_IN_ONE: &str = "R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/bK,11,gP,1/12,gP,gK/12,gP,1/14/3,rR,10/14/14/7,rK,6";
+
+const PROMOTION: &str = "R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/bK,13/5,rP,7,gK/14/14/14/14/14/7,rK,6";
+
+fn custom(variant: &str, rules: &str, fen: &str) -> Replay {
+    let pgn: PGN4 = format!(
+        "[Variant \"{}\"]\n[RuleVariants \"{}\"]\n[StartFen4 \"{}\"]",
+        variant, rules, fen
+    )
+    .parse()
+    .unwrap();
+    Replay::new(pgn.variant().unwrap())
+}
+
+#[test]
+fn regression() {
+    let table: Vec<(&str, Replay, &[u64])> = vec![
+        ("FFA", Replay::new(Variant::ffa_default()), &[20, 399, 7960]),
+        (
+            "Teams",
+            Replay::new(Variant::team_default()),
+            &[20, 399, 7960],
+        ),
+        (
+            "Chess960=4",
+            Replay::from_board(Board::chess960(4), Variant::ffa_default()),
+            &[21, 440, 9219],
+        ),
+        (
+            "Chess960=21",
+            Replay::from_board(Board::chess960(21), Variant::ffa_default()),
+            &[19, 360, 6821],
+        ),
+        (
+            "Chess960=518",
+            Replay::from_board(Board::chess960(518), Variant::ffa_default()),
+            &[20, 399, 7960],
+        ),
+        (
+            "Rook and kings",
+            custom("FFA", "EnPassant", ROOK_AND_KINGS),
+            &[31, 145, 675, 3145],
+        ),
+        (
+            "Rook and kings (Teams)",
+            custom("Teams", "EnPassant", ROOK_AND_KINGS),
+            &[31, 145, 725, 3395],
+        ),
+        (
+            "Mate in one",
+            custom("FFA", "Takeover", MATE_IN_ONE),
+            &[31, 145, 675, 5775],
+        ),
+        (
+            "Promotion",
+            custom("FFA", "EnPassant", PROMOTION),
+            &[6, 29, 137, 661],
+        ),
+    ];
+    for (name, replay, expected) in &table {
+        for (depth, count) in expected.iter().enumerate() {
+            assert_eq!(
+                perft(replay, depth + 1),
+                *count,
+                "{} at depth {}",
+                name,
+                depth + 1
+            );
+        }
+    }
+}
+
+#[test]
+fn divide_by_move() {
+    let replay = Replay::new(Variant::ffa_default());
+    let split = divide(&replay, 2);
+    assert_eq!(split.len(), 20);
+    assert_eq!(split.iter().map(|(_, n)| n).sum::<u64>(), 399);
+    // The pawn on d4 blocks blue's b4-d4
+    let blocking: Move = "d2-d4".parse().unwrap();
+    assert!(split.contains(&(blocking, 19)));
+}
+
+#[test]
+fn divide_matches_perft() {
+    // Red's king can step onto the hill and end the game
+    let fen = "R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/bK,13/13,gK/7,rK,6/14/3,rR,10/14/14/14";
+    let replay = custom("FFA", "KotH", fen);
+    for depth in 1..=2 {
+        let split = divide(&replay, depth);
+        assert_eq!(
+            split.iter().map(|(_, n)| n).sum::<u64>(),
+            perft(&replay, depth),
+            "depth {}",
+            depth
+        );
+    }
+    let hill: Move = "Kh6-h7".parse().unwrap();
+    assert!(divide(&replay, 1).contains(&(hill.clone(), 1)));
+    assert!(divide(&replay, 2).contains(&(hill, 0)));
+}
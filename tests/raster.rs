@@ -0,0 +1,131 @@
+#![cfg(feature = "raster")]
+use fen4::{Board, TurnColor};
+use pgn4::*;
+use std::time::Duration;
+
+const GAME: &str = "[Variant \"FFA\"]
+
+
+
+1. h2-h3 .. b7-c7 .. g13-g12 .. Nn10-l9
+2. e2-e3 ( .. Qg1-j4 )  .. Qa7-b7";
+
+fn pixel(image: &RgbImage, x: u32, y: u32) -> [u8; 3] {
+    let i = ((y * image.width + x) * 3) as usize;
+    [image.pixels[i], image.pixels[i + 1], image.pixels[i + 2]]
+}
+
+#[test]
+fn image() {
+    let image = render_image(&Board::default(), &RasterOptions::default());
+    assert_eq!((image.width, image.height), (448, 448));
+    assert_eq!(image.pixels.len(), 448 * 448 * 3);
+    // Corners are white, d4 is dark and e4 light
+    assert_eq!(pixel(&image, 5, 5), [255, 255, 255]);
+    assert_eq!(pixel(&image, 3 * 32 + 1, 10 * 32 + 1), [0xb5, 0x88, 0x63]);
+    assert_eq!(pixel(&image, 4 * 32 + 1, 10 * 32 + 1), [0xf0, 0xd9, 0xb5]);
+    // Red's king on h1 is a red disc with a white letter
+    assert_eq!(pixel(&image, 7 * 32 + 16, 13 * 32 + 6), [0xbf, 0x3b, 0x43]);
+    let white = (0..32)
+        .flat_map(|dy| (0..32).map(move |dx| (dx, dy)))
+        .filter(|(dx, dy)| pixel(&image, 7 * 32 + dx, 13 * 32 + dy) == [255, 255, 255])
+        .count();
+    assert!(white > 0);
+
+    // Turned around, Yellow's king on g14 is at the bottom in the same spot
+    let yellow = render_image(
+        &Board::default(),
+        &RasterOptions {
+            bottom: TurnColor::Yellow,
+            piece_set: PieceSet::Letters,
+            ..RasterOptions::default()
+        },
+    );
+    assert_eq!(pixel(&yellow, 7 * 32 + 1, 13 * 32 + 1), [0xf0, 0xd9, 0xb5]);
+    assert!((0..32)
+        .flat_map(|dy| (0..32).map(move |dx| (dx, dy)))
+        .any(|(dx, dy)| pixel(&yellow, 7 * 32 + dx, 13 * 32 + dy) == [0xc0, 0x95, 0x26]));
+    // Deterministic
+    assert_eq!(
+        image,
+        render_image(&Board::default(), &RasterOptions::default())
+    );
+}
+
+#[test]
+fn frames() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let options = RasterOptions {
+        square_size: 8,
+        ..RasterOptions::default()
+    };
+    let frames = pgn.render_frames(None, &options).unwrap();
+    assert_eq!(frames.len(), 7);
+    // h2-h3 highlights h2 and h3
+    assert_eq!(pixel(&frames[1], 7 * 8, 12 * 8), [0xf7, 0xec, 0x59]);
+    assert_eq!(pixel(&frames[1], 7 * 8, 11 * 8), [0xf7, 0xec, 0x59]);
+    assert_ne!(pixel(&frames[2], 7 * 8, 11 * 8), [0xf7, 0xec, 0x59]);
+
+    let variation = pgn.render_frames(Some(&[5, 1, 1]), &options).unwrap();
+    assert_eq!(variation.len(), 6);
+    assert_eq!(variation[..5], frames[..5]);
+    assert_ne!(variation[5], frames[5]);
+    assert!(pgn.render_frames(Some(&[9]), &options).is_err());
+}
+
+#[test]
+fn png() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let options = RasterOptions {
+        square_size: 8,
+        ..RasterOptions::default()
+    };
+    let pngs = pgn.png_frames(Some(&[2]), &options).unwrap();
+    assert_eq!(pngs.len(), 3);
+    let decoder = png::Decoder::new(&pngs[2][..]);
+    let mut reader = decoder.read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    assert_eq!((info.width, info.height), (112, 112));
+    let frames = pgn.render_frames(Some(&[2]), &options).unwrap();
+    assert_eq!(buffer[..info.buffer_size()], frames[2].pixels[..]);
+}
+
+#[test]
+fn gif() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let options = RasterOptions {
+        square_size: 8,
+        frame_delay: Duration::from_millis(750),
+        ..RasterOptions::default()
+    };
+    let mut out = Vec::new();
+    pgn.write_gif(None, &options, &mut out).unwrap();
+    assert!(out.starts_with(b"GIF89a"));
+
+    let mut decoder = gif::DecodeOptions::new();
+    decoder.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = decoder.read_info(&out[..]).unwrap();
+    let frames = pgn.render_frames(None, &options).unwrap();
+    let mut count = 0;
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        assert_eq!(frame.delay, 75);
+        let rgb: Vec<u8> = frame
+            .buffer
+            .chunks(4)
+            .flat_map(|c| c[..3].to_vec())
+            .collect();
+        assert_eq!(rgb, frames[count].pixels);
+        count += 1;
+    }
+    assert_eq!(count, 7);
+
+    let huge = RasterOptions {
+        square_size: 5000,
+        ..RasterOptions::default()
+    };
+    assert!(matches!(
+        pgn.write_gif(Some(&[0]), &huge, Vec::new()),
+        Err(RasterError::TooLarge)
+    ));
+}
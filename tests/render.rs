@@ -0,0 +1,161 @@
+use fen4::{Board, Position, TurnColor};
+use pgn4::*;
+
+const GAME: &str = "[Variant \"FFA\"]
+
+
+
+1. h2-h3 .. b7-c7 .. g13-g12 .. Nn10-l9";
+
+#[test]
+fn text_board() {
+    let text = render_text(&Board::default(), &TextOptions::default());
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 15);
+    assert_eq!(lines[0], "14              yR  yN  yB  yK  yQ  yB  yN  yR");
+    assert_eq!(
+        lines[3],
+        "11  bR  bP  .   .   .   .   .   .   .   .   .   .   gP  gR"
+    );
+    assert_eq!(lines[13], " 1              rR  rN  rB  rQ  rK  rB  rN  rR");
+    assert_eq!(
+        lines[14],
+        "    a   b   c   d   e   f   g   h   i   j   k   l   m   n"
+    );
+
+    let bare = TextOptions {
+        coordinates: false,
+        ..TextOptions::default()
+    };
+    let text = render_text(&Board::default(), &bare);
+    assert_eq!(text.lines().count(), 14);
+    assert!(text.starts_with("             yR  yN"));
+}
+
+#[test]
+fn rotation() {
+    let board = Board::default();
+    let bottom_row = |bottom| {
+        let text = render_text(
+            &board,
+            &TextOptions {
+                bottom,
+                ..TextOptions::default()
+            },
+        );
+        let lines: Vec<String> = text.lines().map(str::to_owned).collect();
+        (lines[13].clone(), lines[14].clone())
+    };
+    assert_eq!(
+        bottom_row(TurnColor::Blue),
+        (
+            " a              bR  bN  bB  bK  bQ  bB  bN  bR".to_owned(),
+            "    14  13  12  11  10  9   8   7   6   5   4   3   2   1".to_owned()
+        )
+    );
+    assert_eq!(
+        bottom_row(TurnColor::Yellow).0,
+        "14              yR  yN  yB  yQ  yK  yB  yN  yR"
+    );
+    assert_eq!(
+        bottom_row(TurnColor::Green).0,
+        " n              gR  gN  gB  gK  gQ  gB  gN  gR"
+    );
+}
+
+#[test]
+fn last_move() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let (_, last) = pgn.position_at(&[4]).unwrap();
+    assert_eq!(
+        last,
+        vec![Position { row: 9, col: 13 }, Position { row: 8, col: 11 }]
+    );
+    let text = pgn.render_text_at(&[4], &TextOptions::default()).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines[4],
+        "10  bN  bP  .   .   .   .   .   .   .   .   .   .   gP *."
+    );
+    assert_eq!(
+        lines[5],
+        " 9  bB  bP  .   .   .   .   .   .   .   .   .  *gN  gP  gB"
+    );
+    assert_eq!(pgn.position_at(&[0]).unwrap().1, vec![]);
+    assert!(pgn.render_text_at(&[9], &TextOptions::default()).is_err());
+}
+
+#[test]
+fn ansi() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let options = TextOptions {
+        ansi: true,
+        ..TextOptions::default()
+    };
+    let text = pgn.render_text_at(&[1], &options).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(
+        lines[0],
+        "14          \x1b[33m R \x1b[0m\x1b[33m N \x1b[0m\x1b[33m B \x1b[0m\x1b[33m K \x1b[0m\x1b[33m Q \x1b[0m\x1b[33m B \x1b[0m\x1b[33m N \x1b[0m\x1b[33m R \x1b[0m"
+    );
+    // h2 and h3 are shown in reverse video
+    assert!(lines[11].contains("\x1b[31m\x1b[7m P \x1b[0m"));
+    assert!(lines[12].contains("\x1b[7m . \x1b[0m"));
+    assert_eq!(lines[14], "    a  b  c  d  e  f  g  h  i  j  k  l  m  n");
+}
+
+#[test]
+fn svg() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let options = SvgOptions {
+        arrows: vec![(Position { row: 1, col: 4 }, Position { row: 3, col: 4 })],
+        ..SvgOptions::default()
+    };
+    let svg = pgn.render_svg_at(&[1], &options).unwrap();
+    // Deterministic
+    assert_eq!(svg, pgn.render_svg_at(&[1], &options).unwrap());
+
+    let lines: Vec<&str> = svg.lines().collect();
+    assert_eq!(
+        lines[0],
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="580" height="580" viewBox="0 0 580 580">"#
+    );
+    assert_eq!(lines.last(), Some(&"</svg>"));
+    // 160 squares, 64 pieces and 28 labels
+    assert_eq!(svg.matches("<rect").count(), 160);
+    assert_eq!(svg.matches("<circle").count(), 64);
+    assert_eq!(svg.matches("<text").count(), 64 + 28);
+    // h2 and h3 are highlighted
+    assert_eq!(svg.matches(r##"fill="#f7ec59""##).count(), 2);
+    assert!(svg.contains(r##"<rect x="300" y="480" width="40" height="40" fill="#f7ec59"/>"##));
+    // Red's king on h1
+    assert!(svg.contains(
+        r##"<circle cx="320" cy="540" r="16" fill="#bf3b43" stroke="#222222" stroke-width="1"/>"##
+    ));
+    assert!(svg.contains(r#"<line x1="200" y1="500" x2="200" y2="420""#));
+
+    let plain = render_svg(
+        &Board::default(),
+        &SvgOptions {
+            coordinates: false,
+            bottom: TurnColor::Yellow,
+            ..SvgOptions::default()
+        },
+    );
+    assert!(
+        plain.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="560" height="560""#)
+    );
+    assert!(!plain.contains("<defs>"));
+    assert_eq!(plain.matches("<text").count(), 64);
+    // Yellow's king on g14 is in front of the viewer
+    assert!(plain.contains(r##"<circle cx="300" cy="540" r="16" fill="#c09526""##));
+}
+
+#[test]
+fn svg_dead_pieces() {
+    let board: Board = "R-0,1,0,0-1,1,1,1-1,1,1,1-0,0,0,0-0-3,yK,10/14/14/dbK,13/14/14/14/14/14/14/14/14/14/3,rK,10"
+        .parse()
+        .unwrap();
+    let svg = render_svg(&board, &SvgOptions::default());
+    assert_eq!(svg.matches(r##"fill="#9e9e9e""##).count(), 1);
+}
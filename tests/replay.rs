@@ -0,0 +1,378 @@
+use pgn4::*;
+
+const LONG_TEAM: &str = "1. h2-h3 .. b8-c8 .. g13-g12 .. m7-l7
+2. Qg1xQn8+ .. Qa7xQh14+ .. Kg14xQh14 .. Kn7xQn8
+3. Bi1xBa9 .. Ka8xBa9 .. Bf14xBn6 .. m4-l4
+4. e2-e3 .. Na10-c9 .. Bn6-j10 .. Nn5-l6
+5. Ne1-f3 .. Na5-c6 .. j13-j12 .. Kn8-n7
+6. Nf3-e5 .. Nc6xNe5 .. Bj10xNe5+ .. Nn10-l9
+7. Bf1xb5 .. Ka9-a8 .. Bi14-j13 .. Nl6-k4
+8. Bb5xRa4 .. b11-d11 .. Be5-f6 .. m5-k5
+9. Rd1-e1 .. d11-e11 .. Ne14-d12 .. m11-k11
+10. Ba4-h11 .. e11-f11 .. g12xf11 .. k11xj12
+11. Bh11-g10 .. Ra11-d11 .. k13xj12 .. Rn11-j11
+12. j2-j3 .. Nc9-d7 .. Bf6xb10 .. Nk4-j6
+13. d2-d4 .. Ba6-b5 .. Bb10-j2 .. Rj11-j8
+14. Nj1-i3 .. Rd11-e11 .. e13-e12 .. l4-k4
+15. Ni3xk4 .. Nd7-e9 .. Bj2-m5 .. Rn4xNk4
+16. j3xRk4 .. Bb5xBg10 .. f11xBg10 .. Nj6xk4
+17. Kh1-h2 .. Re11-g11 .. Bm5xRj8 .. Nl9xBj8
+18. Rk1-j1 .. Ne9xg10 .. h13-h12 .. Nj8-k10
+19. Rj1-j10 .. Ng10-i9 .. h12xRg11 .. m9-k9
+20. Rj10-b10 .. Ka8-a9 .. Nd12-b11+ .. m8-k8
+21. Rb10xNk10 .. Ka9-b8 .. Bj13-i12 .. Bn9-m8
+22. Rk10xk9 .. b9-d9 .. Bi12xBm8+ .. Kn7xBm8
+23. Rk9xNi9 .. b6-d6 .. Nj14-k12 .. l7-k7
+24. Re1-j1 .. b7-d7 .. Nk12-l10+ .. Km8-l8
+25. Rj1-j4 .. b4-c4 .. Rk14xk8+ .. Kl8-l7
+26. Rj4xNk4 .. d6-e6 .. Rk8-n8 .. Kl7-k6
+27. Rk4-m4 .. e6-f6 .. Nl10-n9 .. m6-l6
+28. Ri9xd9 .. f6-g6 .. Nn9-m7+ .. Kk6-l7
+29. Rd9xd7 .. g6-h6 .. Nb11-d10 .. m10-k10
+30. Rd7-d6 .. h6-i6 .. Rn8xc8+ .. k5-j5
+31. Rd6-b6+ .. Kb8-a7 .. Rc8-c11 .. k7-j7
+32. O-O#";
+
+const LONG_FFA: &str = "1. j2-j3 .. b7-d7 .. e13-e12 .. m8-k8
+2. Bi1-j2 .. b11-c11 .. j13-j12 .. k8-j8
+3. e2-e3 .. b4-c4 .. Ne14-f12 .. j8-i8
+4. Bf1-e2 .. d7-e7 .. g13-g11 .. i8-h8
+5. h2-h3 .. e7-f7 .. g11-g10 .. m9-l9
+6. Nj1-h2 .. f7-g7 .. Nj14-i12 .. h8-g8=Q
+7. O-O .. g7-h7=Q .. Bf14-e13 .. Qg8-l8
+8. d2-d3 .. c4xd3 .. Ni12-g11 .. m4-k4
+9. Be2xd3 .. Qh7-c7 .. Ng11-h9 .. Ql8-k8
+10. j3xk4 .. Na10-c9 .. O-O .. Qk8xk4
+11. g2-g4 .. Na5-c6 .. Rf14-g14 .. Nn5-l4
+12. Bj2xNl4 .. b8-c8 .. Bi14-j13 .. Qk4xBl4
+13. Ne1-f3 .. Nc6-d8 .. Nh9-i11 .. Qn8-j8+
+14. Kj1-k1 .. Qc7-g7 .. h13-h11 .. Qj8-j2+#
+15. Ra4-f4 .. h11-h10 .. m11-k11
+16. Ba9-c7 .. j12-j11 .. Qj2xj11
+17. b6-c6 .. Be13-f14 .. Qj11xBj13
+18. Qg7-f7 .. Ni11xQj13 .. m10-k10
+19. Nd8-e10 .. Nf12xNe10 .. Bn9xNj13
+20. Qf7xf13+ .. Ke14-d14 .. k11-j11
+21. Nc9xNe10 .. Rk14-j14 .. Bj13-i12
+22. Bc7-b8 .. Qh14-g13 .. m7-l7
+23. Bb8-f12+ .. Qg13xBf12 .. j11-i11
+24. Qa7xQf12+ .. R# .. Bi12xg10
+25. Qf12-f7 .. i11-h11
+26. Rf4-f6 .. Ql4xg4
+27. Rf6xm6 .. Kn7xRm6
+28. Qf7-f6+ .. Bg10-k6
+29. Qf13-f7 .. Rn4-l4
+30. O-O .. Qg4-g10
+31. c8-d8 .. h11-g11=Q
+32. Ne10xQg11 .. Rn11xNg11
+33. Qf7-j3+ .. m5-l5
+34. T";
+
+const TAKEOVER: &str = "[Variant \"FFA\"]
+[RuleVariants \"Takeover\"]
+[StartFen4 \"R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/bK,11,gP,1/12,gP,gK/12,gP,1/14/3,rR,10/14/14/7,rK,6\"]
+
+1. Rd4-n4#";
+
+const DEAD_KING_WALKING: &str = "[Variant \"FFA\"]
+[RuleVariants \"DeadKingWalking\"]
+
+1. R .. b7-c7 .. e13-e12 .. m8-l8
+2. Kh1xh2R .. c7-d7";
+
+#[test]
+fn long_ffa() {
+    let pgn: PGN4 = LONG_FFA.parse().unwrap();
+    let boards = pgn.replay_mainline(&Variant::ffa_default()).unwrap();
+    let last = boards.last().unwrap();
+    assert_eq!(
+        last.dead,
+        [true, true, true, false],
+        "Only green should be left"
+    );
+    assert_eq!(last.turn, fen4::TurnColor::Green);
+}
+
+#[test]
+fn long_team() {
+    let pgn: PGN4 = LONG_TEAM.parse().unwrap();
+    let mut replay = Replay::new(Variant::team_default());
+    let (last, rest) = pgn.turns.split_last().unwrap();
+    for turn in rest {
+        for qturn in &turn.turns {
+            assert_eq!(replay.play(qturn).unwrap(), vec![]);
+        }
+    }
+    // The red king moved on turn 17, so it can't castle
+    assert_eq!(
+        replay.play(&last.turns[0]),
+        Err(ReplayError::IllegalMove("O-O#".into()))
+    );
+}
+
+#[test]
+fn takeover() {
+    use fen4::{Color, Piece, TurnColor};
+    let pgn: PGN4 = TAKEOVER.parse().unwrap();
+    let variant = pgn.variant().unwrap();
+    let boards = pgn.replay_mainline(&variant).unwrap();
+    let last = boards.last().unwrap();
+    assert_eq!(
+        last.dead,
+        [false, false, false, true],
+        "Green should be checkmated"
+    );
+    assert_eq!(last.points[0], 20, "Red should get points for checkmate");
+    assert_eq!(last.turn, TurnColor::Blue);
+    for row in 5..8 {
+        assert_eq!(
+            last.board[row][12],
+            Piece::Normal(Color::Turn(TurnColor::Red), 'P'),
+            "Green's pawns should now belong to red"
+        );
+    }
+    assert_eq!(
+        last.board[6][13],
+        Piece::Normal(Color::Dead(Some(TurnColor::Green)), 'K')
+    );
+
+    let mut no_takeover = variant.clone();
+    no_takeover.ffa_takeover = false;
+    let boards = pgn.replay_mainline(&no_takeover).unwrap();
+    assert_eq!(
+        boards.last().unwrap().board[5][12],
+        Piece::Normal(Color::Dead(Some(TurnColor::Green)), 'P')
+    );
+}
+
+#[test]
+fn dead_king_walking() {
+    use fen4::{Color, Piece, TurnColor};
+    let pgn: PGN4 = DEAD_KING_WALKING.parse().unwrap();
+    let variant = pgn.variant().unwrap();
+    let boards = pgn.replay_mainline(&variant).unwrap();
+    let resigned = &boards[1];
+    assert!(resigned.dead[0]);
+    assert_eq!(
+        resigned.board[0][7],
+        Piece::Normal(Color::Turn(TurnColor::Red), 'K'),
+        "The king keeps walking"
+    );
+    assert_eq!(
+        resigned.board[0][6],
+        Piece::Normal(Color::Dead(Some(TurnColor::Red)), 'Q')
+    );
+    let last = boards.last().unwrap();
+    assert_eq!(
+        last.board[1][7],
+        Piece::Normal(Color::Turn(TurnColor::Red), 'K'),
+        "The king should have captured the dead pawn"
+    );
+    assert_eq!(last.turn, TurnColor::Yellow);
+
+    // Without the rule, the king dies with the rest of the pieces
+    let mut walking_dead = variant.clone();
+    walking_dead.ffa_dead_king_walking = false;
+    assert_eq!(
+        pgn.replay_mainline(&walking_dead),
+        Err(ReplayError::BadModifier("Kh1xh2R".into()))
+    );
+}
+
+#[test]
+fn illegal_moves() {
+    let variant = Variant::ffa_default();
+    let mut replay = Replay::new(variant);
+    let bad = |s: &str| QuarterTurn::from(s.parse::<Move>().unwrap());
+    assert_eq!(
+        replay.play(&bad("b7-c7")),
+        Err(ReplayError::NoPiece("b7".parse().unwrap()))
+    );
+    assert_eq!(
+        replay.play(&bad("h2-h5")),
+        Err(ReplayError::IllegalMove("h2-h5".into()))
+    );
+    assert_eq!(
+        replay.play(&bad("h2xh4")),
+        Err(ReplayError::WrongCapture("h2xh4".into()))
+    );
+    assert_eq!(replay.legal_moves().len(), 20);
+    replay.play(&bad("h2-h4")).unwrap();
+    assert_eq!(replay.board().turn, fen4::TurnColor::Blue);
+}
+
+#[test]
+fn castling_moves_royal() {
+    use fen4::{Color, Piece, Position, TurnColor};
+    let square = |s: &str| s.parse::<Position>().unwrap();
+    let mut board = Variant::ffa_default().initial_board;
+    for empty in &["i1", "j1", "j2"] {
+        let p = square(empty);
+        board.board[p.row][p.col] = Piece::Empty;
+    }
+    let rook = square("i7");
+    board.board[rook.row][rook.col] = Piece::Normal(Color::Turn(TurnColor::Yellow), 'R');
+    board.extra_options.royal[0] = Some(square("h1"));
+
+    let mut replay = Replay::from_board(board, Variant::ffa_default());
+    let play = |replay: &mut Replay, s: &str| {
+        replay
+            .play(&QuarterTurn::from(s.parse::<Move>().unwrap()))
+            .unwrap()
+    };
+    play(&mut replay, "O-O");
+    assert_eq!(replay.board().extra_options.royal[0], Some(square("j1")));
+    play(&mut replay, "b7-c7");
+    play(&mut replay, "Ri7-j7+");
+    assert!(in_check(replay.board(), replay.variant(), TurnColor::Red));
+    // Red has to get out of check on their next turn
+    let red = legal_moves_for(replay.board(), replay.variant(), TurnColor::Red);
+    assert!(!red.iter().any(|m| m.to_string() == "d2-d3"));
+}
+
+const WITH_VARIATION: &str = "[Variant \"FFA\"]
+
+1. h2-h3 .. b7-c7 .. g13-g12
+(1.. R .. m8-l8 )  .. m8-l8
+2. R";
+
+#[test]
+fn fen4_at() {
+    use fen4::{Board, Color, Piece, TurnColor};
+    let pgn: PGN4 = WITH_VARIATION.parse().unwrap();
+    assert_eq!(pgn.fen4_at(&[0]).unwrap(), Board::default());
+
+    let board = pgn.fen4_at(&[3, 1, 1]).unwrap();
+    assert_eq!(board.turn, TurnColor::Green);
+    assert_eq!(board.dead, [false, false, true, false]);
+    assert_eq!(
+        board.board[13][6],
+        Piece::Normal(Color::Dead(Some(TurnColor::Yellow)), 'K')
+    );
+    assert_eq!(board.board[12][6], board.board[12][7], "g13 was not moved");
+
+    let board = pgn.fen4_at(&[3, 1, 2]).unwrap();
+    assert_eq!(board.turn, TurnColor::Red, "Yellow is skipped");
+
+    let board = pgn.fen4_at(&[5]).unwrap();
+    assert_eq!(board.turn, TurnColor::Blue);
+    assert_eq!(board.dead, [true, false, false, false]);
+    assert!(board
+        .to_string()
+        .starts_with("B-1,0,0,0-0,1,1,1-0,1,1,1-0,0,0,0-0-{'resigned':(true,false,false,false)}-"));
+
+    assert_eq!(
+        pgn.fen4_at(&[6]),
+        Err(PositionError::Path(VisitingError::UnexpectedEndOfGame))
+    );
+    assert_eq!(
+        pgn.fen4_at(&[3, 2, 1]),
+        Err(PositionError::Path(VisitingError::InvalidAlternative))
+    );
+}
+
+#[test]
+fn starting_from() {
+    let pgn: PGN4 = format!("[Variant \"FFA\"]\n\n{}", LONG_FFA)
+        .parse()
+        .unwrap();
+    let cut = pgn.starting_from(&[56]).unwrap();
+    assert_eq!(cut.turns[0].to_string(), "1. Ra4-f4 .. h11-h10 .. m11-k11");
+    assert_eq!(cut.turns.last().unwrap().to_string(), "20. T");
+    assert_eq!(cut.fen4_at(&[0]), pgn.fen4_at(&[56]));
+    assert_eq!(cut.fen4_at(&[59]), pgn.fen4_at(&[115]));
+
+    let pgn: PGN4 = WITH_VARIATION.parse().unwrap();
+    let cut = pgn.starting_from(&[1]).unwrap();
+    let expected: PGN4 = "1.. b7-c7 .. g13-g12 ( .. R .. m8-l8 )  .. m8-l8\n2. R"
+        .parse()
+        .unwrap();
+    assert_eq!(cut.turns, expected.turns);
+    assert_eq!(cut.fen4_at(&[2, 1, 2]), pgn.fen4_at(&[3, 1, 2]));
+
+    let cut = pgn.starting_from(&[3, 1, 1]).unwrap();
+    let expected: PGN4 = "1.. m8-l8".parse().unwrap();
+    assert_eq!(cut.turns, expected.turns);
+    assert_eq!(
+        cut.tag("StartFen4"),
+        Some(
+            &pgn.fen4_at(&[3, 1, 1])
+                .unwrap()
+                .to_string()
+                .replace('\n', "")[..]
+        )
+    );
+}
+
+#[test]
+fn hash_matches_board() {
+    let games = [
+        (LONG_FFA, Variant::ffa_default()),
+        (LONG_TEAM, Variant::team_default()),
+        (
+            TAKEOVER,
+            TAKEOVER.parse::<PGN4>().unwrap().variant().unwrap(),
+        ),
+        (
+            DEAD_KING_WALKING,
+            DEAD_KING_WALKING
+                .parse::<PGN4>()
+                .unwrap()
+                .variant()
+                .unwrap(),
+        ),
+    ];
+    for (game, variant) in games {
+        let pgn: PGN4 = game.parse().unwrap();
+        let qturns: Vec<_> = pgn.turns.iter().flat_map(|t| &t.turns).collect();
+        let mut replay = Replay::new(variant);
+        assert_eq!(replay.hash(), zobrist(replay.board()));
+        for (i, qturn) in qturns.iter().enumerate() {
+            if game == LONG_TEAM && i == qturns.len() - 1 {
+                // The red king moved on turn 17, so it can't castle
+                assert_eq!(
+                    replay.play(qturn),
+                    Err(ReplayError::IllegalMove("O-O#".into()))
+                );
+                break;
+            }
+            replay.play(qturn).unwrap();
+            assert_eq!(replay.hash(), zobrist(replay.board()), "after {}", qturn);
+        }
+    }
+}
+
+#[test]
+fn transpositions() {
+    let play = |moves: &[&str]| {
+        let mut replay = Replay::new(Variant::ffa_default());
+        for m in moves {
+            replay
+                .play(&QuarterTurn::from(m.parse::<Move>().unwrap()))
+                .unwrap();
+        }
+        replay
+    };
+    let start = Replay::new(Variant::ffa_default());
+    let knights = play(&[
+        "Nj1-i3", "Na10-c9", "Ne14-f12", "Nn5-l6", "Ni3-j1", "Nc9-a10", "Nf12-e14", "Nl6-n5",
+    ]);
+    assert_eq!(knights.hash(), start.hash(), "The knights went back");
+    assert_ne!(play(&["Nj1-i3"]).hash(), start.hash());
+
+    let one = play(&["h2-h3", "b7-c7", "g13-g12", "m8-l8", "e2-e3"]);
+    let other = play(&["e2-e3", "b7-c7", "g13-g12", "m8-l8", "h2-h3"]);
+    assert_eq!(one.hash(), other.hash());
+
+    // Castling rights are lost by moving the king out and back
+    let king = play(&[
+        "h2-h3", "b7-c7", "g13-g12", "m8-l8", "Kh1-h2", "c7-d7", "Kg14-g13", "l8-k8", "Kh2-h1",
+        "d7-e7", "Kg13-g14", "k8-j8",
+    ]);
+    let knights = play(&[
+        "h2-h3", "b7-c7", "g13-g12", "m8-l8", "Nj1-i3", "c7-d7", "Ne14-f12", "l8-k8", "Ni3-j1",
+        "d7-e7", "Nf12-e14", "k8-j8",
+    ]);
+    assert_eq!(king.board().board, knights.board().board);
+    assert_ne!(king.hash(), knights.hash());
+}
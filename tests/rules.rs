@@ -0,0 +1,205 @@
+use fen4::TurnColor::*;
+use pgn4::*;
+
+const KOTH: &str = "[Variant \"FFA\"]
+[RuleVariants \"KotH\"]
+[StartFen4 \"R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/bK,13/13,gK/6,rK,7/14/14/14/14/14\"]";
+
+const ROOK_AND_KINGS: &str = "R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/bK,13/13,gK/14/14/3,rR,10/14/14/7,rK,6";
+
+const TWO_ROOKS: &str = "R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/bK,13/13,gK/14/14/bR,2,rR,10/14/14/7,rK,6";
+
+const ANTICHESS: &str = "[Variant \"FFA\"]
+[RuleVariants \"Antichess\"]
+[StartFen4 \"R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/14/13,gK/14/14/3,rR,2,bN,7/14/14/7,rK,6\"]";
+
+fn qturn(s: &str) -> QuarterTurn {
+    QuarterTurn::from(s.parse::<Move>().unwrap())
+}
+
+fn with_rules(rules: &str) -> Replay {
+    with_setup("FFA", rules, ROOK_AND_KINGS)
+}
+
+fn with_setup(variant: &str, rules: &str, fen: &str) -> Replay {
+    let pgn: PGN4 = format!(
+        "[Variant \"{}\"]\n[RuleVariants \"{}\"]\n[StartFen4 \"{}\"]",
+        variant, rules, fen
+    )
+    .parse()
+    .unwrap();
+    Replay::new(pgn.variant().unwrap())
+}
+
+#[test]
+fn king_of_the_hill() {
+    let pgn: PGN4 = KOTH.parse().unwrap();
+    let mut replay = Replay::new(pgn.variant().unwrap());
+    assert_eq!(
+        replay.play(&qturn("Kg6-g7")).unwrap(),
+        vec![GameEvent::ReachedHill(Red), GameEvent::GameOver(vec![Red])]
+    );
+}
+
+#[test]
+fn ncheck() {
+    let mut replay = with_rules("1-check");
+    assert_eq!(
+        replay.play(&qturn("Rd4-g4+")).unwrap(),
+        vec![GameEvent::ChecksExhausted(Yellow, Red)]
+    );
+    assert_eq!(replay.board().extra_options.lives, Some([1, 1, 0, 1]));
+    assert!(!replay.in_play(Yellow));
+
+    let mut replay = with_rules("3-check");
+    assert_eq!(replay.play(&qturn("Rd4-g4+")).unwrap(), vec![]);
+    assert_eq!(replay.board().extra_options.lives, Some([3, 3, 2, 3]));
+}
+
+#[test]
+fn capture_the_king() {
+    let mut replay = with_rules("CaptureTheKing");
+    for m in &["Rd4-d8", "Ka8-b8", "Kg14-g13", "Kn7-n6"] {
+        assert_eq!(replay.play(&qturn(m)).unwrap(), vec![]);
+    }
+    assert_eq!(
+        replay.play(&qturn("Rd8xKb8")).unwrap(),
+        vec![GameEvent::KingCaptured(Blue, Red)]
+    );
+    assert_eq!(replay.board().turn, Yellow);
+
+    // Normally the king can't stay in check
+    let mut replay = with_rules("EnPassant");
+    replay.play(&qturn("Rd4-d8+")).unwrap();
+    assert_eq!(
+        replay.play(&qturn("Ka8-b8")),
+        Err(ReplayError::IllegalMove("Ka8-b8".into()))
+    );
+}
+
+#[test]
+fn antichess() {
+    let pgn: PGN4 = ANTICHESS.parse().unwrap();
+    let mut replay = Replay::new(pgn.variant().unwrap());
+    assert_eq!(replay.legal_moves().len(), 1, "Capturing is forced");
+    assert_eq!(
+        replay.play(&qturn("Kh1-h2")),
+        Err(ReplayError::IllegalMove("Kh1-h2".into()))
+    );
+    let stalemating: QuarterTurn = "1. Rd4xNg4S".parse::<PGN4>().unwrap().turns[0].turns[0].clone();
+    assert_eq!(
+        replay.play(&stalemating).unwrap(),
+        vec![GameEvent::Stalemated(Blue), GameEvent::GameOver(vec![Blue])]
+    );
+    assert_eq!(replay.board().turn, Yellow);
+}
+
+#[test]
+fn teammates() {
+    let mut replay = with_setup("Teams", "Teammate=1", ROOK_AND_KINGS);
+    replay.play(&qturn("Rd4-d8")).unwrap();
+    replay
+        .play(&qturn("Ka8-b8"))
+        .expect("A teammate does not give check");
+
+    let mut replay = with_setup("Teams", "EnPassant", ROOK_AND_KINGS);
+    replay.play(&qturn("Rd4-d8+")).unwrap();
+    assert_eq!(
+        replay.play(&qturn("Ka8-b8")),
+        Err(ReplayError::IllegalMove("Ka8-b8".into()))
+    );
+
+    let replay = with_setup("Teams", "Teammate=1", TWO_ROOKS);
+    let capture: Move = "Rd4xRa4".parse().unwrap();
+    assert!(!replay.legal_moves().contains(&capture));
+    let replay = with_setup("Teams", "EnPassant", TWO_ROOKS);
+    assert!(replay.legal_moves().contains(&capture));
+}
+
+#[test]
+fn team_result() {
+    let mut replay = with_setup("Teams", "CaptureTheKing Teammate=1", ROOK_AND_KINGS);
+    for m in &["Rd4-g4", "Ka8-a9", "Kg14-g13", "Kn7-n6"] {
+        assert_eq!(replay.play(&qturn(m)).unwrap(), vec![]);
+    }
+    assert_eq!(
+        replay.play(&qturn("Rg4xKg13")).unwrap(),
+        vec![
+            GameEvent::KingCaptured(Yellow, Red),
+            GameEvent::GameOver(vec![Red, Blue])
+        ]
+    );
+}
+
+#[test]
+fn dead_wall() {
+    let mut replay = with_setup("FFA", "DeadWall", TWO_ROOKS);
+    replay.play(&qturn("R")).unwrap();
+    assert_eq!(
+        replay.play(&qturn("Ra4xRd4")),
+        Err(ReplayError::IllegalMove("Ra4xRd4".into()))
+    );
+
+    let mut replay = with_setup("FFA", "EnPassant", TWO_ROOKS);
+    replay.play(&qturn("R")).unwrap();
+    replay.play(&qturn("Ra4xRd4")).unwrap();
+}
+
+#[test]
+fn repetition() {
+    let pgn: PGN4 = "[Variant \"FFA\"]
+
+1. Nj1-i3 .. Na10-c9 .. Ne14-f12 .. Nn5-l6
+2. Ni3-j1 .. Nc9-a10 .. Nf12-e14 .. Nl6-n5
+3. Nj1-i3 .. Na10-c9 .. Ne14-f12 .. Nn5-l6
+4. Ni3-j1 .. Nc9-a10 .. Nf12-e14 .. Nl6-n5
+5. Nj1-i3"
+        .parse()
+        .unwrap();
+    let draws = pgn.draw_conditions().unwrap();
+    assert_eq!(draws.len(), 18);
+    for (ply, conditions) in draws.iter().enumerate() {
+        match ply {
+            16 | 17 => assert_eq!(conditions, &vec![DrawCondition::Repetition(3)]),
+            _ => assert!(conditions.is_empty(), "Nothing at ply {}", ply),
+        }
+    }
+}
+
+#[test]
+fn fifty_moves() {
+    let pgn: PGN4 = format!(
+        "[Variant \"FFA\"]\n[StartFen4 \"{}\"]\n\n1. Kh1-h2 .. Ka8-a9 .. Kg14-g13",
+        ROOK_AND_KINGS.replacen("-0-", "-198-", 1)
+    )
+    .parse()
+    .unwrap();
+    let draws = pgn.draw_conditions().unwrap();
+    assert_eq!(draws[0], vec![]);
+    assert_eq!(draws[1], vec![]);
+    assert_eq!(draws[2], vec![DrawCondition::FiftyMoves]);
+    assert_eq!(draws[3], vec![DrawCondition::FiftyMoves]);
+}
+
+#[test]
+fn insufficient_material() {
+    use fen4::Board;
+    let with = |fen: &str| {
+        let board: Board = fen.parse().unwrap();
+        pgn4::insufficient_material(&board, &Variant::ffa_default())
+    };
+    assert!(!with(ROOK_AND_KINGS));
+    assert!(with(&ROOK_AND_KINGS.replace("3,rR,10", "14")));
+    assert!(with(&ROOK_AND_KINGS.replace("3,rR,10", "3,rN,10")));
+    assert!(!with(&TWO_ROOKS.replace("bR,2,rR,10", "bN,2,rB,10")));
+    assert!(!pgn4::insufficient_material(
+        &ROOK_AND_KINGS.replace("3,rR,10", "14").parse().unwrap(),
+        &with_rules("CaptureTheKing").variant().clone()
+    ));
+    for rules in &["KotH", "3-check"] {
+        assert!(!pgn4::insufficient_material(
+            &ROOK_AND_KINGS.replace("3,rR,10", "14").parse().unwrap(),
+            &with_rules(rules).variant().clone()
+        ));
+    }
+}
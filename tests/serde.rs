@@ -0,0 +1,95 @@
+#![cfg(feature = "serde")]
+use pgn4::*;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+
+const GAME: &str = "[Variant \"Teams\"]
+[RuleVariants \"EnPassant Chess960=4 PromoteTo=D 3-check\"]
+[Result \"1-0\"]
+
+
+
+1. h2-h4 .. Nb10-d9 { Develops } .. g13-g12 ( .. h13-h12 .. m8-l8
+2. R ( .. e2-e3 )  )  .. O-O
+2. Kh1-i2R .. R# .. T .. S";
+
+fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T) -> String {
+    let json = serde_json::to_string(value).unwrap();
+    let back: T = serde_json::from_str(&json).unwrap();
+    assert_eq!(&back, value, "{}", json);
+    json
+}
+
+#[test]
+fn pgn4() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    round_trip(&pgn);
+    for turn in &pgn.turns {
+        round_trip(turn);
+        for qturn in &turn.turns {
+            round_trip(qturn);
+            round_trip(&qturn.main);
+        }
+    }
+}
+
+#[test]
+fn moves() {
+    let m: Move = "Qg1xQn8+".parse().unwrap();
+    let json = round_trip(&m);
+    assert_eq!(
+        json,
+        r#"{"Normal":{"piece":"Q","from":"g1","captured":"Q","to":"n8","promotion":null,"checks":1,"mates":0}}"#
+    );
+    if let Move::Normal(bm) = &m {
+        round_trip(bm);
+    }
+    for text in ["O-O-O#", "O-O", "R", "T", "S", "#", "f13-f14=D#"] {
+        round_trip(&text.parse::<Move>().unwrap());
+    }
+}
+
+#[test]
+fn positions_are_checked() {
+    let bad = r#"{"piece":"P","from":"z1","captured":null,"to":"d4","promotion":null,"checks":0,"mates":0}"#;
+    assert!(serde_json::from_str::<BasicMove>(bad).is_err());
+}
+
+#[test]
+fn variants() {
+    round_trip(&Variant::ffa_default());
+    round_trip(&Variant::team_default());
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let variant = pgn.variant().unwrap();
+    let json = round_trip(&variant);
+    assert!(json.contains(r#""red_teammate":"y""#), "{}", json);
+    assert!(
+        json.contains(&variant.initial_board.to_string().replace('\n', "")),
+        "{}",
+        json
+    );
+    let json = round_trip(&Variant::ffa_default());
+    assert!(json.contains(r#""red_teammate":"d""#), "{}", json);
+}
+
+#[test]
+fn results_and_reports() {
+    for result in [
+        GameResult::Error,
+        GameResult::Aborted,
+        GameResult::Team(true, false),
+        GameResult::FFA([20, 0, 31, 4]),
+    ] {
+        round_trip(&result);
+    }
+    round_trip(&vec![
+        DrawCondition::Repetition(3),
+        DrawCondition::FiftyMoves,
+        DrawCondition::InsufficientMaterial,
+    ]);
+    let mut pgn: PGN4 = GAME.parse().unwrap();
+    round_trip(&pgn.flatten_variations(FlattenPolicy::Hoist));
+    round_trip(&FlattenPolicy::Drop);
+}
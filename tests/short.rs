@@ -0,0 +1,93 @@
+use pgn4::*;
+
+const OPENING: &str = "[Variant \"Teams\"]
+
+1. h2-h3 .. b8-c8 .. g13-g12 .. m7-l7
+2. Qg1xQn8+ .. Qa7xQh14+ .. Kg14xQh14 .. Kn7xQn8
+3. Bi1xBa9 .. Ka8xBa9 .. Bf14xBn6 .. m4-l4
+4. e2-e3 .. Na10-c9 .. Bn6-j10 .. Nn5-l6
+5. Ne1-f3 .. Na5-c6 .. j13-j12 .. Kn8-n7
+6. Nf3-e5 .. Nc6xNe5 .. Bj10xNe5+ .. Nn10-l9";
+
+const SHORT: &str = "[Variant \"Teams\"]
+
+
+
+1. h3 .. c8 .. g12 .. l7
+2. Qxn8+ .. Qxh14+ .. Kxh14 .. Kxn8
+3. Bxa9 .. Kxa9 .. Bxn6 .. l4
+4. e3 .. Nc9 .. Bj10 .. Nl6
+5. Nf3 .. Nc6 .. j12 .. Kn7
+6. Ne5 .. Nxe5 .. Bxe5+ .. Nl9";
+
+// Red rooks on d4, k4 and d8 and a red pawn on f9 that can take a blue pawn on e10
+const ROOKS: &str = "R-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/4,bP,9/5,rP,8/bK,2,rR,10/13,gK/14/14/3,rR,6,rR,3/14/14/7,rK,6";
+
+fn replay(fen: &str) -> Replay {
+    let pgn: PGN4 = format!("[Variant \"FFA\"]\n[StartFen4 \"{}\"]", fen)
+        .parse()
+        .unwrap();
+    Replay::new(pgn.variant().unwrap())
+}
+
+#[test]
+fn short_notation() {
+    let pgn: PGN4 = OPENING.parse().unwrap();
+    assert_eq!(pgn.short_notation().unwrap().to_string(), SHORT);
+}
+
+#[test]
+fn round_trip() {
+    let pgn: PGN4 = OPENING.parse().unwrap();
+    let mut replay = Replay::new(pgn.variant().unwrap());
+    for qturn in pgn.turns.iter().flat_map(|t| &t.turns) {
+        let short = replay.to_short(&qturn.main);
+        assert_eq!(replay.from_short(&short), Ok(qturn.main.clone()));
+        let long = qturn.main.to_string();
+        assert_eq!(replay.from_short(&long), Ok(qturn.main.clone()));
+        replay.play(qturn).unwrap();
+    }
+}
+
+#[test]
+fn disambiguation() {
+    let replay = replay(ROOKS);
+    let short = |long: &str| replay.to_short(&long.parse().unwrap());
+    assert_eq!(short("Rd4-g4"), "Rdg4");
+    assert_eq!(short("Rk4-g4"), "Rkg4");
+    assert_eq!(short("Rd4-d5"), "R4d5");
+    assert_eq!(short("Rd8-d7"), "R8d7");
+    assert_eq!(short("Rd8-g8"), "Rg8");
+    assert_eq!(short("f9xe10"), "fxe10");
+    assert_eq!(short("Kh1-h2"), "Kh2");
+    assert_eq!(short("Kh1-h3"), "Kh1-h3", "Illegal moves stay long");
+
+    let long = |short: &str| replay.from_short(short).map(|m| m.to_string());
+    assert_eq!(long("Rdg4"), Ok("Rd4-g4".to_owned()));
+    assert_eq!(long("R4d5"), Ok("Rd4-d5".to_owned()));
+    assert_eq!(long("Rd4d5"), Ok("Rd4-d5".to_owned()));
+    assert_eq!(long("Rg8+"), Ok("Rd8-g8+".to_owned()));
+    assert_eq!(long("fxe10"), Ok("f9xe10".to_owned()));
+    assert_eq!(long("Rg4"), Err(ShortError::Ambiguous("Rg4".to_owned())));
+    assert_eq!(long("Rd5"), Err(ShortError::Ambiguous("Rd5".to_owned())));
+    assert_eq!(long("Nf3"), Err(ShortError::NoMatch("Nf3".to_owned())));
+    assert_eq!(long("h2-h5"), Err(ShortError::NoMatch("h2-h5".to_owned())));
+    assert_eq!(long("K"), Err(ShortError::Malformed("K".to_owned())));
+    assert_eq!(long("R"), Ok("R".to_owned()));
+}
+
+#[test]
+fn variations() {
+    let pgn: PGN4 =
+        "[Variant \"FFA\"]\n\n1. h2-h3 .. b7-c7 .. g13-g12\n(1.. R .. m8-l8 )  .. m8-l8\n2. R"
+            .parse()
+            .unwrap();
+    assert_eq!(
+        pgn.short_notation().unwrap().to_string(),
+        "[Variant \"FFA\"]\n\n\n\n1. h3 .. c7 .. g12\n(1.. R .. l8 )  .. l8\n2. R"
+    );
+    let broken: PGN4 = "[Variant \"FFA\"]\n\n1. h2-h4 .. b7-c7 .. g13-g11\n( .. h13-h10 )"
+        .parse()
+        .unwrap();
+    assert!(broken.short_notation().is_err());
+}
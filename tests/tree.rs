@@ -0,0 +1,155 @@
+use fen4::TurnColor;
+use pgn4::*;
+
+const GAME: &str = "[Variant \"FFA\"]
+
+
+
+1. h2-h3 .. b7-c7
+( .. b8-c8 .. g13-g12 ( .. k13-k12 )  .. Nn10-l9 )  .. g13-g12 .. Nn10-l9
+2. e2-e3";
+
+fn summary(moves: impl Iterator<Item = (MoveInfo, String)>) -> Vec<String> {
+    moves
+        .map(|(info, text)| {
+            let side = info.side.map_or("?".to_owned(), |c| c.to_string());
+            format!(
+                "{} {} {} {} {}",
+                info.path, info.depth, info.ply, side, text
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn pre_order() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let all = summary(
+        pgn.moves(MoveFilter::All)
+            .map(|(info, q)| (info, q.main.to_string())),
+    );
+    assert_eq!(
+        all,
+        vec![
+            "1 0 1 R h2-h3",
+            "2 0 2 B b7-c7",
+            "2-1-1 1 2 B b8-c8",
+            "2-1-2 1 3 Y g13-g12",
+            "2-1-2-1-1 2 3 Y k13-k12",
+            "2-1-3 1 4 G Nn10-l9",
+            "3 0 3 Y g13-g12",
+            "4 0 4 G Nn10-l9",
+            "5 0 5 R e2-e3",
+        ]
+    );
+    // Every path leads back to its move
+    for (info, qturn) in pgn.moves(MoveFilter::All) {
+        let (_, last) = pgn.position_at(&info.path).unwrap();
+        if let Move::Normal(m) = &qturn.main {
+            assert_eq!(last, vec![m.from, m.to]);
+        }
+    }
+
+    let mainline: Vec<Path> = pgn
+        .moves(MoveFilter::Mainline)
+        .map(|(i, _)| i.path)
+        .collect();
+    assert_eq!(mainline, (1..=5).map(Path::mainline).collect::<Vec<_>>());
+    let variations: Vec<usize> = pgn
+        .moves(MoveFilter::Variations)
+        .map(|(i, _)| i.depth)
+        .collect();
+    assert_eq!(variations, vec![1, 1, 2, 1]);
+}
+
+#[test]
+fn unknown_side() {
+    let pgn: PGN4 = "1. h2-h3 .. b7-c7".parse().unwrap();
+    assert!(pgn.moves(MoveFilter::All).all(|(i, _)| i.side.is_none()));
+
+    let illegal: PGN4 = "[Variant \"FFA\"]\n\n\n\n1. h2-h5 .. b7-c7"
+        .parse()
+        .unwrap();
+    let sides: Vec<_> = illegal
+        .moves(MoveFilter::All)
+        .map(|(i, _)| i.side)
+        .collect();
+    assert_eq!(sides, vec![Some(TurnColor::Red), None]);
+}
+
+#[test]
+fn side_after_elimination() {
+    let pgn: PGN4 = "[Variant \"FFA\"]
+
+
+
+1. j2-j3 .. b7-d7 .. e13-e12 .. m8-k8
+2. Bi1-j2 .. b11-c11 .. j13-j12 .. k8-j8
+3. e2-e3 .. b4-c4 .. Ne14-f12 .. j8-i8
+4. Bf1-e2 .. d7-e7 .. g13-g11 .. i8-h8
+5. h2-h3 .. e7-f7 .. g11-g10 .. m9-l9
+6. Nj1-h2 .. f7-g7 .. Nj14-i12 .. h8-g8=Q
+7. O-O .. g7-h7=Q .. Bf14-e13 .. Qg8-l8
+8. d2-d3 .. c4xd3 .. Ni12-g11 .. m4-k4
+9. Be2xd3 .. Qh7-c7 .. Ng11-h9 .. Ql8-k8
+10. j3xk4 .. Na10-c9 .. O-O .. Qk8xk4
+11. g2-g4 .. Na5-c6 .. Rf14-g14 .. Nn5-l4
+12. Bj2xNl4 .. b8-c8 .. Bi14-j13 .. Qk4xBl4
+13. Ne1-f3 .. Nc6-d8 .. Nh9-i11 .. Qn8-j8+
+14. Kj1-k1 .. Qc7-g7 .. h13-h11 .. Qj8-j2+#
+15. Ra4-f4 .. h11-h10 .. m11-k11
+16. Ba9-c7 .. j12-j11 .. Qj2xj11"
+        .parse()
+        .unwrap();
+    // Red is checkmated at the end of turn 14, so turn 15 starts with blue
+    let sides: Vec<String> = pgn
+        .moves(MoveFilter::Mainline)
+        .skip(54)
+        .map(|(i, _)| format!("{} {}", i.ply, i.side.unwrap()))
+        .collect();
+    assert_eq!(
+        sides,
+        vec!["55 Y", "56 G", "57 B", "58 Y", "59 G", "60 B", "61 Y", "62 G"]
+    );
+}
+
+#[test]
+fn mutable() {
+    let mut pgn: PGN4 = GAME.parse().unwrap();
+    let expected = summary(
+        pgn.moves(MoveFilter::Variations)
+            .map(|(info, q)| (info, q.main.to_string())),
+    );
+    let found = summary(
+        pgn.moves_mut(MoveFilter::Variations)
+            .map(|(info, q)| (info, q.main.to_string())),
+    );
+    assert_eq!(found, expected);
+
+    for (info, qturn) in pgn.moves_mut(MoveFilter::All) {
+        if info.depth > 0 {
+            *qturn.description = Some(format!("depth {}", info.depth));
+        }
+    }
+    let nested = pgn.turns[0].turns[1].alternatives[0][0].turns[1].alternatives[0][0].turns[0]
+        .description
+        .clone();
+    assert_eq!(nested, Some("depth 2".to_owned()));
+    assert_eq!(pgn.turns[0].turns[2].description, None);
+}
+
+#[test]
+fn side_from_start() {
+    let fen = "B-0,0,0,0-0,0,0,0-0,0,0,0-0,0,0,0-0-6,yK,7/14/14/14/14/14/bK,13/13,gK/14/14/3,rR,10/14/14/7,rK,6";
+    let pgn: PGN4 = format!(
+        "[Variant \"FFA\"]\n[RuleVariants \"EnPassant\"]\n[StartFen4 \"{}\"]\n\n1. Ka8-a9 .. Kg14-g13\n( .. Kg14-h14 )  .. Kn7-n8 .. Rd4-d5\n2. Ka9-a10",
+        fen
+    )
+    .parse()
+    .unwrap();
+    let sides: Vec<String> = pgn
+        .moves(MoveFilter::All)
+        .map(|(i, _)| i.side.unwrap().to_string())
+        .collect();
+    assert_eq!(sides, vec!["B", "Y", "Y", "G", "R", "B"]);
+}
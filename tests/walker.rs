@@ -0,0 +1,147 @@
+use pgn4::*;
+
+const GAME: &str = "[Variant \"FFA\"]
+[Site \"4 Player Chess\"]
+1. h2-h3 { opening } .. b7-c7
+( .. b8-c8 { another } .. g13-g12
+( .. k13-k12 )  .. Nn10-l9 )  .. g13-g12 .. Nn10-l9
+2. e2-e3
+(2.. d2-d3 .. b11-c11 )  .. b10-c10";
+
+/// Writes moves the way Chess.com does, which is what `Display` does too
+#[derive(Default)]
+struct TextWalker {
+    out: String,
+    line_start: bool,
+    turn_start: bool,
+    variation_start: bool,
+}
+
+impl GameWalker for TextWalker {
+    type Error = std::convert::Infallible;
+    fn begin_turn(&mut self, path: &Path, turn: &Turn) -> Result<(), Self::Error> {
+        if self.variation_start {
+            self.out
+                .push_str(if turn.number == 0 { " ( " } else { "\n(" });
+        } else if path.as_slice() != [1] && !self.line_start {
+            self.out.push('\n');
+        }
+        self.variation_start = false;
+        self.line_start = false;
+        self.turn_start = true;
+        if turn.number != 0 {
+            self.out.push_str(&turn.number.to_string());
+        }
+        self.out
+            .push_str(if turn.double_dot { ".. " } else { ". " });
+        Ok(())
+    }
+    fn on_move(&mut self, _path: &Path, qturn: &QuarterTurn) -> Result<(), Self::Error> {
+        if !self.turn_start {
+            self.out.push_str(" .. ");
+        }
+        self.turn_start = false;
+        self.out.push_str(&qturn.main.to_string());
+        Ok(())
+    }
+    fn on_comment(&mut self, _path: &Path, comment: &str) -> Result<(), Self::Error> {
+        self.out.push_str(&format!(" {{ {} }}", comment));
+        Ok(())
+    }
+    fn begin_variation(&mut self, _path: &Path) -> Result<(), Self::Error> {
+        self.variation_start = true;
+        self.line_start = true;
+        Ok(())
+    }
+    fn end_variation(&mut self, _path: &Path) -> Result<(), Self::Error> {
+        self.out.push_str(" ) ");
+        Ok(())
+    }
+}
+
+#[test]
+fn rewrites_display() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let mut walker = TextWalker::default();
+    pgn.walk(&mut walker).unwrap();
+    let written = pgn.to_string();
+    assert_eq!(walker.out, written[written.find("1. ").unwrap()..]);
+}
+
+/// Records every hook call
+#[derive(Default)]
+struct Recorder(Vec<String>);
+
+impl GameWalker for Recorder {
+    type Error = String;
+    fn on_tag(&mut self, key: &str, value: &str) -> Result<(), Self::Error> {
+        self.0.push(format!("tag {}={}", key, value));
+        Ok(())
+    }
+    fn on_move(&mut self, path: &Path, qturn: &QuarterTurn) -> Result<(), Self::Error> {
+        if qturn.main.to_string() == "m4-l4" {
+            return Err(path.to_string());
+        }
+        self.0.push(format!("move {} {}", path, qturn.main));
+        Ok(())
+    }
+    fn on_comment(&mut self, path: &Path, comment: &str) -> Result<(), Self::Error> {
+        self.0.push(format!("comment {} {}", path, comment));
+        Ok(())
+    }
+    fn begin_variation(&mut self, path: &Path) -> Result<(), Self::Error> {
+        self.0.push(format!("begin {}", path));
+        Ok(())
+    }
+    fn end_variation(&mut self, path: &Path) -> Result<(), Self::Error> {
+        self.0.push(format!("end {}", path));
+        Ok(())
+    }
+}
+
+#[test]
+fn hook_order() {
+    let pgn: PGN4 = GAME.parse().unwrap();
+    let mut recorder = Recorder::default();
+    pgn.walk(&mut recorder).unwrap();
+    assert_eq!(
+        recorder.0[..9],
+        [
+            "tag Variant=FFA",
+            "tag Site=4 Player Chess",
+            "move 1 h2-h3",
+            "comment 1 opening",
+            "move 2 b7-c7",
+            "begin 2-1-1",
+            "move 2-1-1 b8-c8",
+            "comment 2-1-1 another",
+            "move 2-1-2 g13-g12",
+        ]
+    );
+    assert_eq!(
+        recorder.0[9..],
+        [
+            "begin 2-1-2-1-1",
+            "move 2-1-2-1-1 k13-k12",
+            "end 2-1-2-1-1",
+            "move 2-1-3 Nn10-l9",
+            "end 2-1-1",
+            "move 3 g13-g12",
+            "move 4 Nn10-l9",
+            "move 5 e2-e3",
+            "begin 5-1-1",
+            "move 5-1-1 d2-d3",
+            "move 5-1-2 b11-c11",
+            "end 5-1-1",
+            "move 6 b10-c10",
+        ]
+    );
+
+    // The first error stops the walk
+    let stopped: PGN4 = "1. d2-d3 .. b11-c11 .. k13-k12 .. m4-l4\n2. h2-h3"
+        .parse()
+        .unwrap();
+    let mut recorder = Recorder::default();
+    assert_eq!(stopped.walk(&mut recorder), Err("4".to_owned()));
+    assert_eq!(recorder.0.len(), 3);
+}
@@ -0,0 +1,96 @@
+use pgn4::*;
+use std::io;
+
+const VARIATIONS: &str = "[Variant \"FFA\"]
+
+
+
+1. h2-h3 .. b7-c7 .. g13-g12 ( .. h13-h12 .. m8-l8
+2. R ( .. e2-e3 )  )  .. Nn10-l9 { Develops the knight }
+2. j2-j3\n(2.. Nj1-i3 .. Na5-c6 )  .. b11-c11";
+
+fn parse(s: &str) -> PGN4 {
+    s.parse().unwrap()
+}
+
+#[test]
+fn chess_com_is_display() {
+    let pgn = parse(VARIATIONS);
+    assert_eq!(WriteOptions::chess_com().format(&pgn), VARIATIONS);
+    assert_eq!(WriteOptions::default().format(&pgn), pgn.to_string());
+
+    let mut out = Vec::new();
+    WriteOptions::default().write(&pgn, &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), VARIATIONS);
+}
+
+#[test]
+fn readable() {
+    let pgn = parse(VARIATIONS);
+    let written = WriteOptions::readable().format(&pgn);
+    assert_eq!(
+        written,
+        "[Variant \"FFA\"]
+
+1. h2-h3 .. b7-c7 .. g13-g12
+  ( .. h13-h12 .. m8-l8
+  2. R
+    ( .. e2-e3 ) )
+.. Nn10-l9 { Develops the knight }
+2. j2-j3
+  (2.. Nj1-i3 .. Na5-c6 )
+.. b11-c11"
+    );
+    assert_eq!(parse(&written), pgn);
+}
+
+#[test]
+fn stripped() {
+    let pgn = parse(VARIATIONS);
+    let options = WriteOptions {
+        descriptions: false,
+        variations: false,
+        blank_lines_after_tags: 0,
+        ..WriteOptions::chess_com()
+    };
+    assert_eq!(
+        options.format(&pgn),
+        "[Variant \"FFA\"]\n1. h2-h3 .. b7-c7 .. g13-g12 .. Nn10-l9\n2. j2-j3 .. b11-c11"
+    );
+}
+
+#[test]
+fn line_width() {
+    let pgn = parse(VARIATIONS);
+    let options = WriteOptions {
+        line_width: Some(20),
+        ..WriteOptions::chess_com()
+    };
+    let written = options.format(&pgn);
+    for line in written.lines() {
+        assert!(
+            line.chars().count() <= 20 || line.contains('{'),
+            "\"{}\" is too long",
+            line
+        );
+    }
+    assert!(written.contains("{ Develops the knight }"));
+    assert_eq!(parse(&written), pgn);
+}
+
+struct Broken;
+impl io::Write for Broken {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn io_errors() {
+    let pgn = parse(VARIATIONS);
+    let error = WriteOptions::default().write(&pgn, Broken).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+}